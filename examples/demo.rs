@@ -2,17 +2,19 @@
 use crate::Section::{Buttons, Components, Containers, Forms, Layouts, PageLayout};
 use denshi::component::button::{Button, Checkbox};
 use denshi::component::layout::{Form, Orientation, Page, Splitter, TabPane};
-use denshi::component::menu::MenuBar;
+use denshi::component::menu::{MenuBar, MenuItem};
 use denshi::component::panel::Panel;
 use denshi::component::text::{TextArea, TextField, TextType};
 use denshi::component::tree::{Tree, TreeModel};
+use denshi::icons::{Icon, MifIcon};
+use denshi::window::WindowOptions;
 use denshi::App;
 use log::{debug, LevelFilter};
 use simplelog::{Config, SimpleLogger};
 use std::error::Error;
 use enclose::enclose;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 enum Section {
     Components,
     Buttons,
@@ -97,20 +99,42 @@ fn build_text_area() -> TextArea {
 fn main() -> Result<(), Box<dyn Error>> {
 
     let mut app = App::new("Demo");
+    app.set_window_options(WindowOptions::new().size(1024, 768).min_size(640, 480));
 
     // init logging
     SimpleLogger::init(LevelFilter::Debug, Config::default())?;
 
     // create menu
     let mut menu = MenuBar::new();
-    menu.add_entry("File");
-    menu.add_entry("Edit");
-    menu.add_entry("Help");
+    menu.add_item(MenuItem::submenu("File", vec![
+        MenuItem::new("Save")
+            .icon(Icon::MifIcon(MifIcon::FloppyDisk))
+            .accelerator("Ctrl+S")
+            .on_click(|_backend| debug!("Save")),
+        MenuItem::separator(),
+        MenuItem::new("Exit")
+            .icon(Icon::MifIcon(MifIcon::Exit))
+            .on_click(|_backend| std::process::exit(0)),
+    ]));
+    menu.add_item(MenuItem::submenu("Edit", vec![
+        MenuItem::new("Copy")
+            .icon(Icon::MifIcon(MifIcon::Copy))
+            .accelerator("Ctrl+C")
+            .on_click(|_backend| debug!("Copy")),
+        MenuItem::new("Paste").accelerator("Ctrl+V").enabled(false),
+        MenuItem::separator(),
+        MenuItem::checkable("Word Wrap", true).on_click(|_backend| debug!("Toggled word wrap")),
+    ]));
+    menu.add_item(MenuItem::submenu("Help", vec![
+        MenuItem::new("About").on_click(|backend| {
+            let _result = backend.eval("Metro.infobox.create('Denshi Demo')");
+        }),
+    ]));
 
     // create tab pane
     let mut tabs = TabPane::new();
 
-    tabs.add_tab("Form", build_form());
+    tabs.add_tab_with_icon("Form", Some(MifIcon::Pencil.into()), build_form());
     tabs.add_tab("Splitter", build_splitter());
     tabs.add_tab("Text Area", build_text_area());
 
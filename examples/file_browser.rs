@@ -1,21 +1,30 @@
 #![windows_subsystem = "windows"]
 use denshi::component::layout::{Orientation, Page, Splitter};
 use denshi::component::panel::Panel;
-use denshi::component::tree::{Tree, TreeModel};
+use denshi::component::tree::{ChildrenLoader, Tree, TreeChange, TreeChangeListener, TreeModel};
 use denshi::App;
 use std::error::Error;
 
 use denshi::component::button::Button;
 use log::LevelFilter;
 use simplelog::{Config, SimpleLogger};
+use std::cell::RefCell;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{self, Path, PathBuf};
 use systemstat::{Platform, System};
 
-struct FileTreeModel {}
+#[derive(Default)]
+struct FileTreeModel {
+    listener: RefCell<Option<TreeChangeListener<PathBuf>>>,
+}
 
 impl FileTreeModel {}
 
+fn read_dir(parent: &Path) -> io::Result<Vec<PathBuf>> {
+    fs::read_dir(parent)?.map(|entry| entry.map(|entry| entry.path())).collect()
+}
+
 impl TreeModel<PathBuf> for FileTreeModel {
     fn roots(&self) -> Vec<PathBuf> {
         let mut entries = Vec::new();
@@ -33,15 +42,14 @@ impl TreeModel<PathBuf> for FileTreeModel {
     }
 
     fn children(&self, parent: &PathBuf) -> Vec<PathBuf> {
-        let mut entries = Vec::new();
-        let read = fs::read_dir(parent);
-        if read.is_ok() {
-            let dir = read.unwrap();
-            for entry in dir {
-                entries.push(entry.unwrap().path())
-            }
-        }
-        entries
+        read_dir(parent).unwrap_or_default()
+    }
+
+    fn children_loader(&self, parent: &PathBuf) -> Option<ChildrenLoader<PathBuf>> {
+        let parent = parent.clone();
+        Some(Box::new(move || {
+            read_dir(&parent).map_err(|read_error| format!("Could not read {}: {}", parent.display(), read_error))
+        }))
     }
 
     fn caption(&self, node: &PathBuf) -> String {
@@ -55,13 +63,37 @@ impl TreeModel<PathBuf> for FileTreeModel {
     fn has_children(&self, node: &PathBuf) -> bool {
         node.is_dir()
     }
+
+    fn rename(&self, node: &PathBuf, name: &str) -> Result<(), String> {
+        if name.is_empty() || name.contains(path::is_separator) {
+            return Err(format!("\"{}\" is not a valid file name", name));
+        }
+        let parent = node.parent().ok_or("A mount point cannot be renamed")?;
+        let target = parent.join(name);
+        if target.exists() {
+            return Err(format!("{} already exists", target.display()));
+        }
+        fs::rename(node, &target).map_err(|error| error.to_string())?;
+        if let Some(listener) = self.listener.borrow().as_ref() {
+            listener(TreeChange::Children(parent.to_path_buf()));
+        }
+        Ok(())
+    }
+
+    fn set_change_listener(&self, listener: TreeChangeListener<PathBuf>) {
+        self.listener.replace(Some(listener));
+    }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     // init logging
     SimpleLogger::init(LevelFilter::Debug, Config::default())?;
 
-    let file_tree = Tree::new(FileTreeModel {});
+    let mut app = App::new("File Browser");
+
+    let file_tree = Tree::new(FileTreeModel::default());
+    file_tree.set_editable(true);
+    file_tree.set_background_loading(Some(&app));
 
     let mut tree_panel = Panel::new(file_tree);
     tree_panel.set_title("File Tree");
@@ -74,7 +106,6 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut page = Page::new();
     page.set_content(split);
 
-    let mut app = App::new("File Browser");
     app.set_content(page);
     app.run()
 }
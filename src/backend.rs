@@ -0,0 +1,77 @@
+use crate::vdom::Document;
+use std::error::Error;
+use web_view::WebView;
+
+/// The target components send their JavaScript to.
+///
+/// The real window is one implementation, `RecordingBackend` another one which
+/// allows testing components without a display.
+pub trait Backend {
+    /// Evaluate a piece of JavaScript in the page.
+    fn eval(&mut self, js: &str) -> Result<(), Box<dyn Error>>;
+
+    /// The page as rendered last, if the backend keeps track of it.
+    fn document(&mut self) -> Option<&mut Document> {
+        None
+    }
+}
+
+impl Backend for WebView<'_, ()> {
+    fn eval(&mut self, js: &str) -> Result<(), Box<dyn Error>> {
+        WebView::eval(self, js)?;
+        Ok(())
+    }
+}
+
+/// The window of a running app together with the page as rendered last.
+pub(crate) struct WindowBackend<'a, 'b> {
+    pub(crate) webview: &'a mut WebView<'b, ()>,
+    pub(crate) document: &'a mut Document,
+}
+
+impl Backend for WindowBackend<'_, '_> {
+    fn eval(&mut self, js: &str) -> Result<(), Box<dyn Error>> {
+        Backend::eval(self.webview, js)
+    }
+
+    fn document(&mut self) -> Option<&mut Document> {
+        Some(self.document)
+    }
+}
+
+/// A backend keeping all evaluated scripts in memory.
+#[derive(Default)]
+pub struct RecordingBackend {
+    scripts: Vec<String>,
+    document: Document,
+}
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        RecordingBackend {
+            scripts: Vec::new(),
+            document: Document::new(),
+        }
+    }
+
+    /// All scripts evaluated so far.
+    pub fn scripts(&self) -> &[String] {
+        &self.scripts
+    }
+
+    /// Return all scripts evaluated so far and forget them.
+    pub fn take_scripts(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.scripts)
+    }
+}
+
+impl Backend for RecordingBackend {
+    fn eval(&mut self, js: &str) -> Result<(), Box<dyn Error>> {
+        self.scripts.push(js.into());
+        Ok(())
+    }
+
+    fn document(&mut self) -> Option<&mut Document> {
+        Some(&mut self.document)
+    }
+}
@@ -1,8 +1,11 @@
-use crate::component::Component;
-use crate::event::Event;
+use crate::component::{dirty_update_hook, event_handler, remember_rendering, Bound, Component, Dirty};
+use crate::event::{Event, EventValue};
+use crate::frp::{self, Behavior, Sink};
+use crate::html::{Element, Node};
+use crate::icons::Icon;
 use crate::utils::create_id;
 use log::warn;
-use web_view::WebView;
+use crate::backend::Backend;
 use std::rc::Rc;
 use std::cell::RefCell;
 
@@ -11,21 +14,31 @@ use std::cell::RefCell;
 pub struct Button {
     id: String,
     state: Rc<RefCell<ButtonState>>,
+    dirty: Dirty,
     click_event: Rc<RefCell<Option<Box<dyn Fn()>>>>,
+    clicks: frp::Event<()>,
+    click_sink: Sink<()>,
 }
 
 struct ButtonState {
-    label: String,
+    label: Bound<String>,
+    icon: Option<Icon>,
 }
 
 impl Button {
-    pub fn new(label: impl Into<String>) -> Button {
+    pub fn new(label: impl Into<Behavior<String>>) -> Button {
+        let (clicks, click_sink) = frp::Event::new_with_sink();
+        let dirty = Dirty::default();
         Button {
             id: create_id(),
             state: Rc::new(RefCell::new(ButtonState {
-                label: label.into(),
+                label: Bound::new(label.into(), &dirty),
+                icon: None,
             })) ,
+            dirty,
             click_event: Default::default(),
+            clicks,
+            click_sink,
         }
     }
 
@@ -33,22 +46,47 @@ impl Button {
         self.click_event.borrow_mut().replace(Box::new(event));
     }
 
+    /// The clicks on the button.
+    pub fn clicks(&self) -> frp::Event<()> {
+        self.clicks.clone()
+    }
+
+    /// Change the label, either to a fixed text or to a `Behavior`, e.g.
+    /// `text_field.text()` or `event.hold(initial)` to follow an `Event`.
+    ///
+    /// A running app shows the current label after every event it handled.
+    pub fn set_label(&self, label: impl Into<Behavior<String>>) {
+        self.state.borrow_mut().label = Bound::new(label.into(), &self.dirty);
+    }
+
+    /// Show an icon in front of the label.
+    pub fn set_icon(&self, icon: Option<Icon>) {
+        self.state.borrow_mut().icon = icon;
+        self.dirty.mark();
+    }
 }
 
 impl Component for Button {
-    fn render(&mut self) -> String {
-        format!(
-            r#"<a id="{id}" class="button" onclick="fire_clicked('{id}')">{label}</a>"#,
-            id = self.id,
-            label = self.state.borrow().label,
-        )
+    fn render(&mut self) -> Node {
+        let state = self.state.borrow();
+        let mut button = Element::new("a")
+            .id(&self.id)
+            .class("button")
+            .on("click", format!("fire_clicked('{id}')", id = self.id))
+            .on_event(event_handler(self))
+            .on_update(dirty_update_hook(self, &self.dirty));
+        if let Some(icon) = &state.icon {
+            button = button.child(icon.render());
+        }
+        button.text(state.label.value()).into()
     }
 
-    fn handle_event(&mut self, _webview: &mut WebView<()>, event: &Event) {
+    fn handle_event(&mut self, _backend: &mut dyn Backend, event: &Event) {
         if event.id == self.id {
+            self.click_sink.send(&());
             if let Some(listener) = self.click_event.borrow_mut().as_ref() {
                 listener()
-            } else {
+            } else if !self.clicks.has_listeners() {
                 warn!(target: "button" , "No listener for button with ID {}", self.id);
             }
         }
@@ -59,42 +97,215 @@ impl Component for Button {
     }
 }
 
+/// The state of a check box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CheckState {
+    Unchecked,
+    Checked,
+    /// Neither checked nor unchecked, e.g. for a partially selected group.
+    /// Only set by the app, a click by the user checks the box.
+    Indeterminate,
+}
+
+/// The look of a check box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CheckboxStyle {
+    /// A box with a check mark.
+    Checkbox,
+    /// A toggle switch.
+    Switch,
+}
+
+type CheckListener = Box<dyn Fn(bool)>;
+
 /// A check box with a label.
 #[derive(Clone)]
 pub struct Checkbox {
     id: String,
-    state: Rc<RefCell<CheckboxState>>
+    style: CheckboxStyle,
+    state: Rc<RefCell<CheckboxState>>,
+    dirty: Dirty,
+    change_event: Rc<RefCell<Option<CheckListener>>>,
+    changes: frp::Event<bool>,
+    change_sink: Sink<bool>,
+    /// the states set by the app
+    sets: frp::Event<bool>,
+    set_sink: Sink<bool>,
 }
 
 struct CheckboxState {
-    label: String,
+    label: Bound<String>,
+    check_state: CheckState,
 }
 
 impl Checkbox {
-    pub fn new(label: impl Into<String>) -> Self {
+    pub fn new(label: impl Into<Behavior<String>>) -> Self {
+        Checkbox::new_with_style(label, CheckboxStyle::Checkbox)
+    }
+
+    pub fn new_with_style(label: impl Into<Behavior<String>>, style: CheckboxStyle) -> Self {
+        let (changes, change_sink) = frp::Event::new_with_sink();
+        let (sets, set_sink) = frp::Event::new_with_sink();
+        let dirty = Dirty::default();
         Checkbox {
             id: create_id(),
+            style,
             state: Rc::new(RefCell::new(CheckboxState {
-                label: label.into(),
+                label: Bound::new(label.into(), &dirty),
+                check_state: CheckState::Unchecked,
             })),
+            dirty,
+            change_event: Default::default(),
+            changes,
+            change_sink,
+            sets,
+            set_sink,
         }
     }
+
+    /// Change the label, either to a fixed text or to a `Behavior`.
+    ///
+    /// A running app shows the current label after every event it handled.
+    pub fn set_label(&self, label: impl Into<Behavior<String>>) {
+        self.state.borrow_mut().label = Bound::new(label.into(), &self.dirty);
+    }
+
+    /// Whether the check box is checked.
+    pub fn is_checked(&self) -> bool {
+        self.state.borrow().check_state == CheckState::Checked
+    }
+
+    /// Whether the check box is checked, changing when the user or the app
+    /// (un)checked the box.
+    pub fn checked(&self) -> Behavior<bool> {
+        self.changes.merge(&self.sets).hold(self.is_checked())
+    }
+
+    /// The current state of the check box.
+    pub fn check_state(&self) -> CheckState {
+        self.state.borrow().check_state
+    }
+
+    /// Check or uncheck the box. A running app shows it after every event it
+    /// handled, call `refresh` to show it right away.
+    pub fn set_checked(&self, checked: bool) {
+        self.set_check_state(if checked { CheckState::Checked } else { CheckState::Unchecked });
+    }
+
+    /// Change the state of the check box, see `set_checked`.
+    pub fn set_check_state(&self, check_state: CheckState) {
+        let was_checked = self.is_checked();
+        self.state.borrow_mut().check_state = check_state;
+        self.dirty.mark();
+        if self.is_checked() != was_checked {
+            self.set_sink.send(&!was_checked);
+        }
+    }
+
+    /// Call `listener` whenever the user (un)checked the box.
+    pub fn set_change_event(&self, listener: impl Fn(bool) + 'static) {
+        self.change_event.borrow_mut().replace(Box::new(listener));
+    }
+
+    /// The states the user switched the box to.
+    pub fn changes(&self) -> frp::Event<bool> {
+        self.changes.clone()
+    }
 }
 
 impl Component for Checkbox {
-    fn render(&mut self) -> String {
-        format!(
-            r#"<input id="{id}" type="checkbox" data-role="checkbox" data-on-checkbox-create="fire_created" data-caption="{label}">"#,
-            id = self.id,
-            label = self.state.borrow().label
-        )
+    fn render(&mut self) -> Node {
+        let state = self.state.borrow();
+        let role = match self.style {
+            CheckboxStyle::Checkbox => "checkbox",
+            CheckboxStyle::Switch => "switch",
+        };
+        let checkbox = Element::new("input")
+            .id(&self.id)
+            .attr("type", "checkbox")
+            .data("role", role)
+            .data(&format!("on-{}-create", role), "fire_created")
+            .data("indeterminate", state.check_state == CheckState::Indeterminate)
+            .caption("data-caption", &state.label.value())
+            .on("change", format!("fire_checked_changed('{id}')", id = self.id))
+            .on_event(event_handler(self))
+            .on_update(dirty_update_hook(self, &self.dirty));
+        if state.check_state == CheckState::Checked {
+            checkbox.attr("checked", "checked").into()
+        } else {
+            checkbox.into()
+        }
     }
 
-    fn handle_event(&mut self, _webview: &mut WebView<()>, event: &Event) {
-        if event.id == self.id {}
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event) {
+        if event.id == self.id {
+            if let EventValue::CheckedChanged(checked) = event.value {
+                // the page shows the new state already
+                self.state.borrow_mut().check_state = if checked { CheckState::Checked } else { CheckState::Unchecked };
+                remember_rendering(self, backend);
+                if let Some(listener) = self.change_event.borrow().as_ref() {
+                    listener(checked)
+                }
+                self.change_sink.send(&checked);
+            }
+        }
     }
 
     fn id(&self) -> String {
         self.id.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::RecordingBackend;
+    use enclose::enclose;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_refresh() {
+        let mut button = Button::new("Start");
+        button.set_label("Stop <now>");
+
+        let mut backend = RecordingBackend::new();
+        button.refresh(&mut backend).unwrap();
+
+        assert_eq!(backend.scripts(), [format!(
+            r#"replace_element('{id}', '\x3Ca id=\"{id}\" class=\"button\" onclick=\"fire_clicked(&#39;{id}&#39;)\"\x3EStop &lt;now&gt;\x3C/a\x3E')"#,
+            id = button.id()
+        )]);
+    }
+
+    #[test]
+    fn test_checkbox() {
+        let mut checkbox = Checkbox::new_with_style("Dark", CheckboxStyle::Switch);
+        let changes = checkbox.changes().hold(false);
+        let mut backend = RecordingBackend::new();
+        backend.document().unwrap().set_content(checkbox.render());
+
+        checkbox.set_check_state(CheckState::Indeterminate);
+        assert!(!checkbox.is_checked());
+        checkbox.refresh(&mut backend).unwrap();
+        assert_eq!(backend.take_scripts(), [format!("set_attribute('{}', 'data-indeterminate', 'true')", checkbox.id())]);
+
+        checkbox.handle_event(&mut backend, &Event { id: checkbox.id(), value: EventValue::CheckedChanged(true) });
+        assert!(checkbox.checked().value());
+        assert!(changes.value());
+        assert!(backend.take_scripts().is_empty());
+
+        let checked = checkbox.checked();
+        let fired = Rc::new(Cell::new(0));
+        checked.changes().unwrap().listen(enclose!((fired) move |_| fired.set(fired.get() + 1)));
+        checkbox.set_checked(true);
+        assert_eq!(fired.get(), 0);
+        checkbox.set_checked(false);
+        assert_eq!(fired.get(), 1);
+        assert!(!checked.value());
+        checkbox.refresh(&mut backend).unwrap();
+        assert_eq!(backend.take_scripts(), [
+            format!("remove_attribute('{}', 'checked')", checkbox.id()),
+            format!("set_checked('{}', false)", checkbox.id()),
+        ]);
+    }
+}
@@ -1,7 +1,9 @@
 use crate::component::{Component, ComponentManager};
 use crate::event::Event;
+use crate::html::{Element, Node};
+use crate::icons::Icon;
 use crate::utils::create_id;
-use web_view::WebView;
+use crate::backend::Backend;
 use std::rc::Rc;
 use std::cell::RefCell;
 
@@ -55,29 +57,27 @@ impl Splitter {
 }
 
 impl Component for Splitter {
-    fn render(&mut self) -> String {
+    fn render(&mut self) -> Node {
         let split_mode = match self.orientation {
-            Orientation::HORIZONTAL => "data-split-mode=\"horizontal\"",
-            Orientation::VERTICAL => "data-split-mode=\"vertical\"",
+            Orientation::HORIZONTAL => "horizontal",
+            Orientation::VERTICAL => "vertical",
         };
 
         let mut state = self.state.borrow_mut();
 
-        format!(
-            r#"<div id="{id}" data-gutter-size="{gutter}" data-role="splitter" class="h-100" {split_mode}>
-                      <div>{first}</div>
-                      <div>{second}</div>
-                   </div>"#,
-            id = self.id,
-            first = state.comps.render_component(&SplitterPosition::First),
-            second = state.comps.render_component(&SplitterPosition::Second),
-            split_mode = split_mode,
-            gutter = state.gutter_size,
-        )
+        Element::new("div")
+            .id(&self.id)
+            .data("gutter-size", state.gutter_size)
+            .data("role", "splitter")
+            .class("h-100")
+            .data("split-mode", split_mode)
+            .child(Element::new("div").children(state.comps.render_component(&SplitterPosition::First)))
+            .child(Element::new("div").children(state.comps.render_component(&SplitterPosition::Second)))
+            .into()
     }
 
-    fn handle_event(&mut self, webview: &mut WebView<()>, event: &Event) {
-        self.state.borrow_mut().comps.notify_all_components(webview, event);
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event) {
+        self.state.borrow_mut().comps.notify_all_components(backend, event);
     }
 
     fn id(&self) -> String {
@@ -126,33 +126,29 @@ impl Page {
 }
 
 impl Component for Page {
-    fn render(&mut self) -> String {
-        let mut components = String::new();
-        components.push_str(
-            "<div class=\"noselect h-100 container-fluid d-flex flex-column flex-align-stretch\">",
-        );
-
+    fn render(&mut self) -> Node {
         let mut state = self.state.borrow_mut();
 
-        components.push_str(&state.comps.render_component_with(&PagePosition::Header, |comp_str| {
-            format!("<header>{header}</header>", header = comp_str)
-        }));
-        components.push_str(&state.comps.render_component_with(&PagePosition::Content, |comp_str| {
-            format!("<div class=\"h-100\">{content}</div>", content = comp_str)
-        }));
-        components.push_str(&state.comps.render_component_with(&PagePosition::Footer, |comp_str| {
-            format!("<footer>{footer}</footer>", footer = comp_str)
-        }));
-
-        components.push_str("</div>");
-        components
+        Element::new("div")
+            .id(&self.id)
+            .class("noselect h-100 container-fluid d-flex flex-column flex-align-stretch")
+            .children(state.comps.render_component_with(&PagePosition::Header, |comp_node| {
+                Element::new("header").child(comp_node).into()
+            }))
+            .children(state.comps.render_component_with(&PagePosition::Content, |comp_node| {
+                Element::new("div").class("h-100").child(comp_node).into()
+            }))
+            .children(state.comps.render_component_with(&PagePosition::Footer, |comp_node| {
+                Element::new("footer").child(comp_node).into()
+            }))
+            .into()
     }
 
-    fn handle_event(&mut self, webview: &mut WebView<()>, event: &Event) {
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event) {
 
         let mut state = self.state.borrow_mut();
 
-        state.comps.notify_all_components(webview, event);
+        state.comps.notify_all_components(backend, event);
     }
 
     fn id(&self) -> String {
@@ -185,32 +181,25 @@ impl Form {
         self.state.borrow_mut().comps.add_component((), component)
     }
 
-    fn render_lines(&mut self) -> String {
-        let mut lines = String::new();
+    fn render_lines(&mut self) -> Vec<Node> {
         let mut state = self.state.borrow_mut();
 
-        lines.push_str(&state.comps.render_component_with(&(), |comp_str| {
-            format!(
-                "<div class=\"form-group\">{line}</div>",
-                line = comp_str
-            )
-        }));
-        lines
+        state.comps.render_component_with(&(), |comp_node| {
+            Element::new("div").class("form-group").child(comp_node).into()
+        })
     }
 }
 
 impl Component for Form {
-    fn render(&mut self) -> String {
-        let lines = self.render_lines();
-        format!(
-            "<form id=\"{id}\">{lines}</form>",
-            id = self.id(),
-            lines = lines,
-        )
+    fn render(&mut self) -> Node {
+        Element::new("form")
+            .id(self.id())
+            .children(self.render_lines())
+            .into()
     }
 
-    fn handle_event(&mut self, webview: &mut WebView<()>, event: &Event) {
-        self.state.borrow_mut().comps.notify_all_components(webview, event);
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event) {
+        self.state.borrow_mut().comps.notify_all_components(backend, event);
     }
 
     fn id(&self) -> String {
@@ -227,6 +216,7 @@ pub struct TabPane {
 
 struct Tab {
     label: String,
+    icon: Option<Icon>,
     content: Box<dyn Component>,
 }
 
@@ -239,58 +229,59 @@ impl TabPane {
     }
 
     pub fn add_tab(&mut self, label: impl Into<String>, content: impl Component + 'static) {
+        self.add_tab_with_icon(label, None, content);
+    }
+
+    /// Add a tab showing an icon in front of its label.
+    pub fn add_tab_with_icon(&mut self, label: impl Into<String>, icon: Option<Icon>, content: impl Component + 'static) {
         self.tabs.borrow_mut().push(Tab {
             label: label.into(),
+            icon,
             content: Box::new(content),
         });
     }
 
-    fn render_tab_headers(&self) -> String {
-        let mut tabs = String::new();
+    fn render_tab_headers(&self) -> Element {
+        let mut tabs = Element::new("ul")
+            .data("role", "tabs")
+            .data("expand", true);
         for tab in &*self.tabs.borrow() {
-            tabs.push_str(
-                format!(
-                    "<li><a href=\"#{id}tab\">{label}</a></li>",
-                    id = tab.content.id(),
-                    label = tab.label
-                )
-                .as_str(),
-            );
+            let mut link = Element::new("a").attr("href", format!("#{id}tab", id = tab.content.id()));
+            if let Some(icon) = &tab.icon {
+                link = link.child(icon.render());
+            }
+            tabs = tabs.child(Element::new("li").child(link.text(tab.label.as_str())));
         }
         tabs
     }
 
-    fn render_tab_content(&mut self) -> String {
-        let mut tabs = String::new();
-        tabs.push_str("<div class=\"border bd-default no-border-top p-2 w-100 h-100\">");
+    fn render_tab_content(&mut self) -> Element {
+        let mut tabs = Element::new("div").class("border bd-default no-border-top p-2 w-100 h-100");
         for tab in &mut *self.tabs.borrow_mut() {
-            tabs.push_str(
-                format!(
-                    "<div class=\"w-100 h-100\" id=\"{id}tab\">",
-                    id = tab.content.id()
-                )
-                .as_str(),
+            tabs = tabs.child(
+                Element::new("div")
+                    .class("w-100 h-100")
+                    .id(format!("{id}tab", id = tab.content.id()))
+                    .child(tab.content.render()),
             );
-            tabs.push_str(tab.content.render().as_str());
-            tabs.push_str("</div>");
         }
-        tabs.push_str("</div>");
         tabs
     }
 }
 
 impl Component for TabPane {
-    fn render(&mut self) -> String {
-        format!(
-            r#"<ul data-role="tabs" data-expand="true">{tabs}</ul>{content}"#,
-            tabs = self.render_tab_headers(),
-            content = self.render_tab_content()
-        )
+    fn render(&mut self) -> Node {
+        Element::new("div")
+            .id(&self.id)
+            .class("w-100 h-100")
+            .child(self.render_tab_headers())
+            .child(self.render_tab_content())
+            .into()
     }
 
-    fn handle_event(&mut self, webview: &mut WebView<()>, event: &Event) {
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event) {
         for tab in &mut *self.tabs.borrow_mut() {
-            tab.content.handle_event(webview, event);
+            tab.content.handle_event(backend, event);
         }
     }
 
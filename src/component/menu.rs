@@ -1,19 +1,221 @@
-use crate::component::menu::MenuItem::Entry;
-use crate::component::Component;
-use crate::event::Event;
+use crate::component::{event_handler, Component, Dirty};
+use crate::event::{Event, EventValue};
+use crate::html::{Element, Node};
+use crate::icons::Icon;
 use crate::utils::create_id;
-use web_view::WebView;
+use crate::backend::Backend;
+use log::warn;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use enclose::enclose;
 
-enum MenuItem {
-    Entry(String),
+/// Callback for a clicked menu item.
+pub type MenuAction = Rc<dyn Fn(&mut dyn Backend)>;
+
+#[derive(Clone)]
+enum MenuItemKind {
+    Entry,
+    Checkable(Rc<Cell<bool>>),
+    Submenu(Vec<MenuItem>),
+    Separator,
+}
+
+/// An entry of a menu.
+///
+/// Clones share their checked and enabled state, so a clone kept by the app can
+/// change the item after it has been added to a menu.
+#[derive(Clone)]
+pub struct MenuItem {
+    id: String,
+    label: String,
+    kind: MenuItemKind,
+    icon: Option<Icon>,
+    accelerator: Option<String>,
+    enabled: Rc<Cell<bool>>,
+    action: Option<MenuAction>,
+    dirty: Dirty,
+}
+
+impl MenuItem {
+    /// An entry to be clicked.
+    pub fn new(label: impl Into<String>) -> Self {
+        MenuItem {
+            id: create_id(),
+            label: label.into(),
+            kind: MenuItemKind::Entry,
+            icon: None,
+            accelerator: None,
+            enabled: Rc::new(Cell::new(true)),
+            action: None,
+            dirty: Dirty::default(),
+        }
+    }
+
+    /// An entry toggling a check mark when clicked.
+    pub fn checkable(label: impl Into<String>, checked: bool) -> Self {
+        MenuItem {
+            kind: MenuItemKind::Checkable(Rc::new(Cell::new(checked))),
+            ..MenuItem::new(label)
+        }
+    }
+
+    /// An entry opening a nested menu.
+    pub fn submenu(label: impl Into<String>, items: Vec<MenuItem>) -> Self {
+        MenuItem {
+            kind: MenuItemKind::Submenu(items),
+            ..MenuItem::new(label)
+        }
+    }
+
+    /// A line separating groups of entries.
+    pub fn separator() -> Self {
+        MenuItem {
+            kind: MenuItemKind::Separator,
+            ..MenuItem::new("")
+        }
+    }
+
+    /// Show an icon in front of the label.
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Show a keyboard shortcut, e.g. `Ctrl+S`, next to the label.
+    ///
+    /// Only the label is shown, the shortcut itself is not handled.
+    pub fn accelerator(mut self, accelerator: impl Into<String>) -> Self {
+        self.accelerator = Some(accelerator.into());
+        self
+    }
+
+    /// Allow the entry to be clicked.
+    pub fn enabled(self, enabled: bool) -> Self {
+        self.enabled.set(enabled);
+        self
+    }
+
+    /// Call `action` when the entry is clicked.
+    pub fn on_click(mut self, action: impl Fn(&mut dyn Backend) + 'static) -> Self {
+        self.action = Some(Rc::new(action));
+        self
+    }
+
+    /// The id the entry is identified with in the page.
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    /// Enable or disable the entry. A running app shows it after every event it handled.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+        self.dirty.mark();
+    }
+
+    /// Whether a checkable entry is checked.
+    pub fn is_checked(&self) -> bool {
+        match &self.kind {
+            MenuItemKind::Checkable(checked) => checked.get(),
+            _ => false,
+        }
+    }
+
+    /// Check or uncheck a checkable entry. A running app shows it after every event it handled.
+    pub fn set_checked(&self, checked: bool) {
+        if let MenuItemKind::Checkable(state) = &self.kind {
+            state.set(checked);
+            self.dirty.mark();
+        }
+    }
+
+    /// Find the entry with the given id in this entry and its submenus.
+    fn find(&self, id: &str) -> Option<&MenuItem> {
+        if self.id == id {
+            return Some(self);
+        }
+        match &self.kind {
+            MenuItemKind::Submenu(items) => items.iter().find_map(|item| item.find(id)),
+            _ => None,
+        }
+    }
+
+    /// Whether this entry or an entry of its submenus changed, clearing the marks.
+    fn take_changes(&self) -> bool {
+        let changed = self.dirty.take();
+        match &self.kind {
+            MenuItemKind::Submenu(items) => items.iter().fold(changed, |changed, item| item.take_changes() || changed),
+            _ => changed,
+        }
+    }
+
+    /// Handle a click on this entry.
+    fn click(&self, backend: &mut dyn Backend) {
+        if !self.is_enabled() {
+            return;
+        }
+        if let MenuItemKind::Checkable(checked) = &self.kind {
+            self.set_checked(!checked.get());
+        }
+        if let Some(action) = &self.action {
+            action(backend)
+        }
+    }
+
+    fn render(&self, menu_id: &str) -> Node {
+        let mut item = Element::new("li").id(&self.id);
+        if !self.is_enabled() {
+            item = item.class("disabled");
+        }
+
+        let mut link = Element::new("a").attr("href", "#");
+        let icon = match &self.kind {
+            MenuItemKind::Checkable(checked) if checked.get() => {
+                Some(Element::new("span").class("mif-checkmark icon").into())
+            }
+            _ => self.icon.as_ref().map(Icon::render),
+        };
+        if let Some(icon) = icon {
+            link = link.child(icon);
+        }
+        link = link.text(self.label.as_str());
+        if let Some(accelerator) = &self.accelerator {
+            link = link.child(Element::new("span").class("hotkey").text(accelerator.as_str()));
+        }
+
+        match &self.kind {
+            MenuItemKind::Separator => item.class("divider").into(),
+            MenuItemKind::Submenu(items) => item
+                .child(link.class("dropdown-toggle"))
+                .child(render_menu(items, menu_id))
+                .into(),
+            MenuItemKind::Entry | MenuItemKind::Checkable(_) => item
+                .child(link.on(
+                    "click",
+                    format!("fire_menu_item_clicked('{menu}', '{item}')", menu = menu_id, item = self.id),
+                ))
+                .into(),
+        }
+    }
+}
+
+/// Render the drop down list of a submenu.
+fn render_menu(items: &[MenuItem], menu_id: &str) -> Node {
+    Element::new("ul")
+        .class("d-menu")
+        .data("role", "dropdown")
+        .children(items.iter().map(|item| item.render(menu_id)))
+        .into()
 }
 
 #[derive(Clone)]
 pub struct MenuBar {
     id: String,
     entries: Rc<RefCell<Vec<MenuItem>>>,
+    dirty: Dirty,
 }
 
 impl MenuBar {
@@ -21,38 +223,103 @@ impl MenuBar {
         MenuBar {
             id: create_id(),
             entries: Rc::new(RefCell::new(Vec::new())),
+            dirty: Dirty::default(),
         }
     }
 
     pub fn add_entry(&mut self, label: impl Into<String>) {
-        self.entries.borrow_mut().push(Entry(label.into()));
+        self.add_item(MenuItem::new(label));
     }
 
-    fn render_items(&self) -> String {
-        let mut items = String::new();
-        for entry in &*self.entries.borrow() {
-            match entry {
-                Entry(label) => items.push_str(
-                    format!("<li><a href=\"#\">{label}</a></li>", label = label).as_str(),
-                ),
-            }
-        }
-        items
+    /// Add an entry, usually a submenu, to the bar.
+    pub fn add_item(&mut self, item: MenuItem) {
+        self.entries.borrow_mut().push(item);
+        self.dirty.mark();
+    }
+
+    /// Find an entry by its id.
+    fn find(&self, id: &str) -> Option<MenuItem> {
+        self.entries.borrow().iter().find_map(|item| item.find(id)).cloned()
+    }
+
+    /// Whether the bar or one of its entries changed, clearing the marks.
+    fn take_changes(&self) -> bool {
+        let changed = self.dirty.take();
+        self.entries.borrow().iter().fold(changed, |changed, item| item.take_changes() || changed)
+    }
+
+    fn render_items(&self) -> Vec<Node> {
+        self.entries.borrow().iter().map(|item| item.render(&self.id)).collect()
     }
 }
 
 impl Component for MenuBar {
-    fn render(&mut self) -> String {
-        format!(
-            r#"<ul class="h-menu">{items}</ul>"#,
-            items = self.render_items()
-        )
+    fn render(&mut self) -> Node {
+        self.take_changes();
+        Element::new("ul")
+            .id(&self.id)
+            .class("h-menu")
+            .on_event(event_handler(self))
+            .on_update(enclose!((self => menu) Rc::new(move |backend| {
+                if menu.take_changes() {
+                    menu.clone().refresh(backend)
+                } else {
+                    Ok(())
+                }
+            })))
+            .children(self.render_items())
+            .into()
     }
 
-    fn handle_event(&mut self, _webview: &mut WebView<()>, _event: &Event) {}
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event) {
+        if event.id == self.id {
+            if let EventValue::MenuItemClicked(item_id) = &event.value {
+                match self.find(item_id) {
+                    Some(item) => item.click(backend),
+                    None => warn!(target: "menu", "No menu item with ID {}", item_id),
+                }
+            }
+        }
+    }
 
     fn id(&self) -> String {
         self.id.clone()
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::RecordingBackend;
+    use crate::icons::MifIcon;
+
+    #[test]
+    fn test_menu() {
+        let saved = Rc::new(Cell::new(0));
+        let save = MenuItem::new("Save")
+            .icon(Icon::MifIcon(MifIcon::FloppyDisk))
+            .accelerator("Ctrl+S")
+            .on_click(enclose!((saved) move |_backend| saved.set(saved.get() + 1)));
+        let wrap = MenuItem::checkable("Word wrap", false);
+        let print = MenuItem::new("Print").enabled(false);
+        let mut menu = MenuBar::new();
+        menu.add_item(MenuItem::submenu("File", vec![
+            save.clone(),
+            MenuItem::separator(),
+            MenuItem::submenu("View", vec![wrap.clone(), print.clone()]),
+        ]));
+
+        let html = menu.render().to_string();
+        assert!(html.contains(r#"<span class="mif-floppy-disk icon"></span>Save<span class="hotkey">Ctrl+S</span>"#));
+        assert!(html.contains(&format!(r#"<li id="{}" class="disabled">"#, print.id())));
+
+        let mut backend = RecordingBackend::new();
+        for item in &[&save, &wrap, &print] {
+            let value = EventValue::MenuItemClicked(item.id());
+            menu.handle_event(&mut backend, &Event { id: menu.id(), value });
+        }
+        assert_eq!(saved.get(), 1);
+        assert!(wrap.is_checked());
+        assert!(menu.render().to_string().contains("mif-checkmark"));
+    }
+}
@@ -1,7 +1,14 @@
-use crate::event::Event;
-use web_view::WebView;
+use crate::event::{Event, EventHandler, UpdateHook};
+use crate::html::Node;
+use crate::vdom::Patch;
+use crate::backend::Backend;
+use crate::frp::{Behavior, Listener};
+use enclose::enclose;
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::error::Error;
 use std::hash::Hash;
+use std::rc::Rc;
 
 pub mod button;
 pub mod layout;
@@ -13,11 +20,135 @@ pub mod tree;
 /// Functions every component needs to provide.
 pub trait Component {
     /// Render the component as HTML.
-    fn render(&mut self) -> String;
+    fn render(&mut self) -> Node;
     /// Callback for events.
-    fn handle_event(&mut self, webview: &mut WebView<()>, event: &Event);
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event);
     /// The globally unique ID for teh component.
     fn id(&self) -> String;
+    /// Render the component again and update it in the live page.
+    ///
+    /// If the backend knows the previous rendering only the changes are sent,
+    /// otherwise the complete component is replaced.
+    fn refresh(&mut self, backend: &mut dyn Backend) -> Result<(), Box<dyn Error>> {
+        let id = self.id();
+        let node = self.render();
+        let patches = match backend.document() {
+            Some(document) => document.update(&id, node),
+            None => vec![Patch::Replace {
+                id,
+                html: node.to_string(),
+            }],
+        };
+        for patch in patches {
+            backend.eval(&patch.to_js())?;
+        }
+        Ok(())
+    }
+}
+
+/// Create a handler passing events to the component.
+///
+/// Attached to the root element of a component with `Element::on_event`,
+/// events fired with the component's id get routed directly to it.
+pub fn event_handler<C: Component + Clone + 'static>(component: &C) -> EventHandler {
+    let component = component.clone();
+    Rc::new(move |backend, event| component.clone().handle_event(backend, event))
+}
+
+/// Remember the current rendering of a component without sending it to the page.
+///
+/// Used when the page changed the component itself, e.g. the user typed into a
+/// text field, so the update hooks do not send the change back.
+pub(crate) fn remember_rendering(component: &mut dyn Component, backend: &mut dyn Backend) {
+    let id = component.id();
+    let node = component.render();
+    if let Some(document) = backend.document() {
+        document.update(&id, node);
+    }
+}
+
+/// Create a hook refreshing the component after events which changed it.
+///
+/// Attached to the root element of a component with `Element::on_update`, the
+/// page follows the `Behavior`s the component is bound to. Rendering the
+/// component clears `dirty`.
+pub(crate) fn dirty_update_hook<C: Component + Clone + 'static>(component: &C, dirty: &Dirty) -> UpdateHook {
+    dirty.marked.set(false);
+    let component = component.clone();
+    let dirty = dirty.clone();
+    Rc::new(move |backend| {
+        if dirty.take() {
+            component.clone().refresh(backend)
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Whether a component changed since it has been rendered last.
+///
+/// Marked by the setters of the component and by changes of the behaviors it
+/// shows, clones share the flag.
+#[derive(Clone, Default)]
+pub(crate) struct Dirty {
+    marked: Rc<Cell<bool>>,
+    /// the number of shown values which cannot tell their changes.
+    polled: Rc<Cell<usize>>,
+}
+
+impl Dirty {
+    pub(crate) fn mark(&self) {
+        self.marked.set(true);
+    }
+
+    /// Consider the component changed after every event, for shown values
+    /// changing unnoticed, e.g. a bound model. Undone when their `Bound` is dropped.
+    pub(crate) fn poll(&self) {
+        self.polled.set(self.polled.get() + 1);
+    }
+
+    /// Whether the component changed, clearing the mark.
+    pub(crate) fn take(&self) -> bool {
+        self.marked.replace(false) || self.polled.get() > 0
+    }
+}
+
+/// A behavior shown by a component, marking it dirty whenever the value changed.
+pub(crate) struct Bound<T> {
+    behavior: Behavior<T>,
+    listener: Option<Listener>,
+    dirty: Dirty,
+}
+
+impl<T> Bound<T> {
+    pub(crate) fn new(behavior: Behavior<T>, dirty: &Dirty) -> Self {
+        dirty.mark();
+        let listener = match behavior.changes() {
+            Some(changes) => Some(changes.listen(enclose!((dirty) move |_| dirty.mark()))),
+            None => {
+                dirty.poll();
+                None
+            }
+        };
+        Bound {
+            behavior,
+            listener,
+            dirty: dirty.clone(),
+        }
+    }
+
+    pub(crate) fn value(&self) -> T {
+        self.behavior.value()
+    }
+}
+
+impl<T> Drop for Bound<T> {
+    fn drop(&mut self) {
+        match self.listener.take() {
+            Some(listener) => listener.unlisten(),
+            None => self.dirty.polled.set(self.dirty.polled.get() - 1),
+        }
+    }
 }
 
 pub struct ComponentManager<K> {
@@ -44,28 +175,28 @@ impl<K: Eq + Hash + Clone> ComponentManager<K> {
         comp_vec.unwrap().push(Box::new(comp));
     }
 
-    pub fn render_component(&mut self, key: &K) -> String {
-        self.render_component_with(key, |comp_str| {
-            comp_str.into()
+    pub fn render_component(&mut self, key: &K) -> Vec<Node> {
+        self.render_component_with(key, |comp_node| {
+            comp_node
         })
     }
 
-    pub fn render_component_with(&mut self, key: &K, f: impl Fn(&str)->String ) -> String {
+    pub fn render_component_with(&mut self, key: &K, f: impl Fn(Node)->Node ) -> Vec<Node> {
         let comp_vec = self.components.get_mut(key);
         if comp_vec.is_none() {
-            return "".into();
+            return Vec::new();
         }
-        let mut all_comp_str = String::new();
+        let mut all_comp_nodes = Vec::new();
         for comp in comp_vec.unwrap() {
-            all_comp_str.push_str( f(&comp.render()).as_str())
+            all_comp_nodes.push(f(comp.render()))
         }
-        all_comp_str
+        all_comp_nodes
     }
 
-    pub fn notify_all_components(&mut self, webview: &mut WebView<()>, event: &Event) {
+    pub fn notify_all_components(&mut self, backend: &mut dyn Backend, event: &Event) {
         for value in self.components.values_mut() {
             for comp in value {
-                comp.handle_event(webview, event)
+                comp.handle_event(backend, event)
             }
         }
     }
@@ -1,7 +1,9 @@
 use crate::component::Component;
 use crate::event::Event;
+use crate::html::{Element, Node};
+use crate::icons::Icon;
 use crate::utils::create_id;
-use web_view::WebView;
+use crate::backend::Backend;
 use std::rc::Rc;
 use std::cell::RefCell;
 
@@ -31,6 +33,7 @@ pub struct Panel {
 
 struct PanelState {
     title: Option<String>,
+    title_icon: Option<Icon>,
     content: Box<dyn Component>,
 }
 
@@ -40,6 +43,7 @@ impl Panel {
             id: create_id(),
             state: Rc::new(RefCell::new(PanelState {
                 title: None,
+                title_icon: None,
                 content: Box::new(content),
             })),
             collapsible: false,
@@ -55,33 +59,38 @@ impl Panel {
         self.state.borrow_mut().title = Some(title.into())
     }
 
-    pub fn set_collapsible(&mut self, collapsible: bool) {
-        self.collapsible = collapsible
+    /// Show an icon in the title bar.
+    pub fn set_title_icon(&mut self, icon: Option<Icon>) {
+        self.state.borrow_mut().title_icon = icon
     }
-}
 
-fn optional_attribute(attribute: &str, value: &Option<String>) -> String {
-    match value {
-        Some(s) => format!("{attr}=\"{value}\"", attr = attribute, value = s),
-        None => "".to_string(),
+    pub fn set_collapsible(&mut self, collapsible: bool) {
+        self.collapsible = collapsible
     }
 }
 
 impl Component for Panel {
-    fn render(&mut self) -> String {
+    fn render(&mut self) -> Node {
         let mut state = self.state.borrow_mut();
-        format!(
-            r#"<div style="overflow: {scroll_mode};" id="{id}" class="h-100 w-100" {title} data-collapsible="{collapsible}" data-role="panel">{content}</div>"#,
-            id = self.id,
-            content = state.content.render(),
-            title = optional_attribute("data-title-caption", &state.title),
-            collapsible = self.collapsible,
-            scroll_mode = self.scroll_mode.style()
-        )
+        let mut panel = Element::new("div")
+            .style(format!("overflow: {scroll_mode};", scroll_mode = self.scroll_mode.style()))
+            .id(&self.id)
+            .class("h-100 w-100");
+        if let Some(title) = &state.title {
+            panel = panel.caption("data-title-caption", title);
+        }
+        if let Some(icon) = &state.title_icon {
+            panel = panel.data("title-icon", icon.render());
+        }
+        panel
+            .data("collapsible", self.collapsible)
+            .data("role", "panel")
+            .child(state.content.render())
+            .into()
     }
 
-    fn handle_event(&mut self, webview: &mut WebView<()>, event: &Event) {
-        self.state.borrow_mut().content.handle_event(webview, event)
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event) {
+        self.state.borrow_mut().content.handle_event(backend, event)
     }
 
     fn id(&self) -> String {
@@ -1,56 +1,165 @@
-use crate::component::Component;
+use crate::component::{dirty_update_hook, event_handler, remember_rendering, Bound, Component, Dirty};
 use crate::event::{Event, EventValue};
+use crate::html::{Element, Node};
 use crate::utils::create_id;
-use web_view::WebView;
+use crate::backend::Backend;
 use log::debug;
 use std::rc::Rc;
 use std::cell::RefCell;
-use crate::frp::Behavior;
+use crate::frp::{self, Behavior, Sink};
 use enclose::enclose;
 
+type ChangeListener = Box<dyn Fn(&str)>;
+
+/// Listeners for changes of a text by the user.
+#[derive(Clone)]
+struct TextChanges {
+    change_event: Rc<RefCell<Option<ChangeListener>>>,
+    changes: frp::Event<String>,
+    change_sink: Sink<String>,
+}
+
+impl TextChanges {
+    fn new() -> Self {
+        let (changes, change_sink) = frp::Event::new_with_sink();
+        TextChanges {
+            change_event: Default::default(),
+            changes,
+            change_sink,
+        }
+    }
+
+    fn notify(&self, text: &str) {
+        if let Some(listener) = self.change_event.borrow().as_ref() {
+            listener(text)
+        }
+        self.change_sink.send(&text.to_string());
+    }
+}
+
+/// The text shown by a text component, optionally kept in sync with a model.
+struct ShownText {
+    text: Bound<String>,
+    model: Option<Rc<RefCell<String>>>,
+}
+
+impl ShownText {
+    fn new(dirty: &Dirty) -> Self {
+        ShownText {
+            text: Bound::new(Behavior::constant(String::new()), dirty),
+            model: None,
+        }
+    }
+
+    fn value(&self) -> String {
+        self.text.value()
+    }
+
+    /// Show `text`, replacing the model bound before.
+    fn show(&mut self, text: Behavior<String>, dirty: &Dirty) {
+        self.text = Bound::new(text, dirty);
+        self.model = None;
+    }
+
+    /// Take the text entered by the user, writing it to the model if there is one.
+    fn set(&mut self, text: String, dirty: &Dirty) {
+        match &self.model {
+            Some(model) => {
+                *model.borrow_mut() = text;
+                dirty.mark();
+            }
+            None => self.text = Bound::new(Behavior::constant(text), dirty),
+        }
+    }
+
+    /// Show the text of `model`, replacing the model bound before.
+    fn bind(&mut self, model: Rc<RefCell<String>>, dirty: &Dirty) {
+        self.text = Bound::new(Behavior::from_fn(enclose!((model) move || model.borrow().clone())), dirty);
+        self.model = Some(model);
+    }
+}
+
 /// A text area containing multi line text.
 #[derive(Clone)]
 pub struct TextArea {
     id: String,
     state: Rc<RefCell<TextAreaState>>,
+    dirty: Dirty,
+    changes: TextChanges,
 }
 
 struct TextAreaState {
-    text: String,
+    text: ShownText,
 }
 
 impl TextArea {
     pub fn new() -> Self {
+        let dirty = Dirty::default();
         TextArea {
             id: create_id(),
             state: Rc::new(RefCell::new(TextAreaState {
-                text: "".into(),
+                text: ShownText::new(&dirty),
             })),
+            dirty,
+            changes: TextChanges::new(),
         }
     }
 
     pub fn text(&self) -> Behavior<String> {
         Behavior::from_fn(enclose!((self => sclone) move || {
-            sclone.state.borrow().text.clone()
+            sclone.state.borrow().text.value()
         }))
     }
 
+    /// Change the text, either to a fixed text or to a `Behavior`, replacing a bound model.
+    ///
+    /// A running app shows the current text after every event it handled, call
+    /// `refresh` to show it right away.
+    pub fn set_text(&self, text: impl Into<Behavior<String>>) {
+        self.state.borrow_mut().text.show(text.into(), &self.dirty);
+    }
+
+    /// Call `listener` whenever the user changed the text.
+    pub fn set_change_event(&self, listener: impl Fn(&str) + 'static) {
+        self.changes.change_event.borrow_mut().replace(Box::new(listener));
+    }
+
+    /// The texts entered by the user.
+    pub fn changes(&self) -> frp::Event<String> {
+        self.changes.changes.clone()
+    }
+
+    /// Keep the text in sync with a model: changes by the user are written to it
+    /// and changes of the model are shown after every event.
+    pub fn bind(&self, model: Rc<RefCell<String>>) {
+        self.state.borrow_mut().text.bind(model, &self.dirty);
+    }
 }
 
 impl Component for TextArea {
-    fn render(&mut self) -> String {
-        format!(
-            r#"<textarea id="{id}" class="w-100 h-100" data-default-value="{text}" data-on-change="fire_value_changed('{id}')" data-on-textarea-create="fire_created" data-role="textarea" >{text}</textarea>"#,
-            id = self.id,
-            text = self.state.borrow().text,
-        )
+    fn render(&mut self) -> Node {
+        let text = self.state.borrow().text.value();
+        Element::new("textarea")
+            .id(&self.id)
+            .class("w-100 h-100")
+            .data("on-change", format!("fire_value_changed('{id}')", id = self.id))
+            .data("on-textarea-create", "fire_created")
+            .data("role", "textarea")
+            .on_event(event_handler(self))
+            .on_update(dirty_update_hook(self, &self.dirty))
+            .text(text)
+            .into()
     }
 
-    fn handle_event(&mut self, _webview: &mut WebView<()>, event: &Event) {
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event) {
         if event.id == self.id {
             debug!(target: "textarea", "Received event: {:?}", event);
             match &event.value {
-                EventValue::ValueChanged(new_value) => self.state.borrow_mut().text=new_value.clone(),
+                EventValue::ValueChanged(new_value) => {
+                    self.state.borrow_mut().text.set(new_value.clone(), &self.dirty);
+                    remember_rendering(self, backend);
+                    self.changes.notify(new_value);
+                }
                 _ => (),
             }
         }
@@ -72,11 +181,11 @@ pub enum TextType {
 }
 
 impl TextType {
-    fn to_attribute(&self) -> String {
+    fn apply_attributes(&self, element: Element) -> Element {
         match self {
-            TextType::Text => "type=\"text\"".into(),
-            TextType::Password { allow_reveal} => format!("type=\"password\" data-reveal-button=\"{reveal}\"", reveal = allow_reveal),
-            TextType::Email => "type=\"email\"".into(),
+            TextType::Text => element.attr("type", "text"),
+            TextType::Password { allow_reveal} => element.attr("type", "password").data("reveal-button", allow_reveal),
+            TextType::Email => element.attr("type", "email"),
         }
     }
 }
@@ -87,59 +196,98 @@ pub struct TextField {
     id: String,
     text_type: TextType,
     state: Rc<RefCell<TextFiledState>>,
+    dirty: Dirty,
+    changes: TextChanges,
 }
 
 struct TextFiledState {
-    label: String,
-    text: String,
+    label: Bound<String>,
+    text: ShownText,
 }
 
 impl TextField {
-    pub fn new(label: impl Into<String>) -> Self {
-        TextField {
-            id: create_id(),
-            state: Rc::new(RefCell::new(TextFiledState {
-                label: label.into(),
-                text: "".into(),
-            })),
-            text_type: TextType::Text,
-        }
+    pub fn new(label: impl Into<Behavior<String>>) -> Self {
+        TextField::new_with_type(label, TextType::Text)
     }
 
-    pub fn new_with_type(label: impl Into<String>, text_type: TextType) -> Self {
+    pub fn new_with_type(label: impl Into<Behavior<String>>, text_type: TextType) -> Self {
+        let dirty = Dirty::default();
         TextField {
             id: create_id(),
             state: Rc::new(RefCell::new(TextFiledState {
-                label: label.into(),
-                text: "".into(),
+                label: Bound::new(label.into(), &dirty),
+                text: ShownText::new(&dirty),
             })),
+            dirty,
+            changes: TextChanges::new(),
             text_type,
         }
     }
 
     pub fn text(&self) -> Behavior<String> {
         Behavior::from_fn(enclose!((self => sclone) move || {
-            sclone.state.borrow().text.clone()
+            sclone.state.borrow().text.value()
         }))
     }
+
+    /// Change the text, either to a fixed text or to a `Behavior`, replacing a bound model.
+    ///
+    /// A running app shows the current text after every event it handled, call
+    /// `refresh` to show it right away.
+    pub fn set_text(&self, text: impl Into<Behavior<String>>) {
+        self.state.borrow_mut().text.show(text.into(), &self.dirty);
+    }
+
+    /// Call `listener` whenever the user changed the text.
+    pub fn set_change_event(&self, listener: impl Fn(&str) + 'static) {
+        self.changes.change_event.borrow_mut().replace(Box::new(listener));
+    }
+
+    /// The texts entered by the user.
+    pub fn changes(&self) -> frp::Event<String> {
+        self.changes.changes.clone()
+    }
+
+    /// Keep the text in sync with a model: changes by the user are written to it
+    /// and changes of the model are shown after every event.
+    pub fn bind(&self, model: Rc<RefCell<String>>) {
+        self.state.borrow_mut().text.bind(model, &self.dirty);
+    }
+
+    /// Change the label, either to a fixed text or to a `Behavior`.
+    ///
+    /// A running app shows the current label after every event it handled.
+    pub fn set_label(&self, label: impl Into<Behavior<String>>) {
+        self.state.borrow_mut().label = Bound::new(label.into(), &self.dirty);
+    }
 }
 
 impl Component for TextField {
-    fn render(&mut self) -> String {
-        format!(
-            r#"<input id="{id}" value="{value}" oninput="fire_value_changed('{id}')" data-on-clear-click="fire_value_changed('{id}')" {type_attr} data-role="input" data-prepend="{label}"/>"#,
-            id = self.id,
-            label = self.state.borrow().label,
-            value = self.state.borrow().text,
-            type_attr = self.text_type.to_attribute(),
-        )
+    fn render(&mut self) -> Node {
+        let state = self.state.borrow();
+        let fire_changed = format!("fire_value_changed('{id}')", id = self.id);
+        let input = Element::new("input")
+            .id(&self.id)
+            .attr("value", state.text.value())
+            .on("input", &fire_changed)
+            .data("on-clear-click", &fire_changed);
+        self.text_type.apply_attributes(input)
+            .data("role", "input")
+            .caption("data-prepend", &state.label.value())
+            .on_event(event_handler(self))
+            .on_update(dirty_update_hook(self, &self.dirty))
+            .into()
     }
 
-    fn handle_event(&mut self, _webview: &mut WebView<()>, event: &Event) {
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event) {
         if event.id == self.id {
             debug!(target: "textfield", "Received event: {:?}", event);
             match &event.value {
-                EventValue::ValueChanged(new_value) => self.state.borrow_mut().text = new_value.clone() ,
+                EventValue::ValueChanged(new_value) => {
+                    self.state.borrow_mut().text.set(new_value.clone(), &self.dirty);
+                    remember_rendering(self, backend);
+                    self.changes.notify(new_value);
+                }
                 _ => (),
             }
         }
@@ -149,3 +297,30 @@ impl Component for TextField {
         self.id.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebind() {
+        let field = TextField::new("Name");
+        field.bind(Rc::new(RefCell::new("Joe".to_string())));
+        let model = Rc::new(RefCell::new("Ann".to_string()));
+        field.bind(model.clone());
+        assert_eq!(field.dirty.polled.get(), 1);
+
+        field.set_text("Bob");
+        assert_eq!(*model.borrow(), "Ann");
+        assert_eq!(field.text().value(), "Bob");
+        assert_eq!(field.dirty.polled.get(), 0);
+
+        let (names, name_sink) = frp::Event::new_with_sink();
+        field.set_text(names.hold("Eve".to_string()));
+        assert!(field.dirty.take());
+        assert!(!field.dirty.take());
+        name_sink.send(&"Max".to_string());
+        assert!(field.dirty.take());
+        assert_eq!(field.text().value(), "Max");
+    }
+}
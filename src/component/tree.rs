@@ -1,11 +1,21 @@
-use crate::component::Component;
+use crate::component::button::CheckState;
+use crate::component::{event_handler, Component, Dirty};
+use crate::escape::{escape_html_text, escape_js_string};
 use crate::event::Event;
 use crate::event::EventValue::*;
+use crate::html::{Element, Node};
+use crate::icons::Icon;
 use crate::utils::create_id;
 use log::warn;
-use web_view::WebView;
+use crate::backend::Backend;
+use crate::App;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::any::Any;
+use std::error::Error;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use enclose::enclose;
 
 /// Trait to provide date for the tree
 pub trait TreeModel<U> {
@@ -13,43 +23,141 @@ pub trait TreeModel<U> {
     fn roots(&self) -> Vec<U>;
     /// get a node's children
     fn children(&self, parent: &U) -> Vec<U>;
+    /// a job loading a node's children, to be used instead of `children` for
+    /// slow sources like large or network directories. Trees with background
+    /// loading run it on a worker thread, others run it right away. An error
+    /// message returned by the job is shown to the user.
+    fn children_loader(&self, _parent: &U) -> Option<ChildrenLoader<U>> {
+        None
+    }
     // get the label to be displayed for a node.
     fn caption(&self, node: &U) -> String;
     /// has this nod children
     fn has_children(&self, node: &U) -> bool;
+    /// the icon shown in front of a node's caption.
+    fn icon(&self, _node: &U) -> Option<Icon> {
+        None
+    }
+    /// the text shown when the mouse rests on a node.
+    fn tooltip(&self, _node: &U) -> Option<String> {
+        None
+    }
+    /// additional CSS classes of a node.
+    fn css_class(&self, _node: &U) -> Option<String> {
+        None
+    }
+    /// can a node be selected and clicked. Disabled nodes are greyed out.
+    fn is_enabled(&self, _node: &U) -> bool {
+        true
+    }
+    /// rename a node after the user edited its caption. An error rejects the
+    /// name, it is shown to the user who keeps editing.
+    fn rename(&self, _node: &U, _name: &str) -> Result<(), String> {
+        Err("The node cannot be renamed".into())
+    }
+    /// can `source` be dropped onto `target`. Nodes are never dropped onto
+    /// themselves or their descendants.
+    fn can_drop(&self, _source: &U, _target: &U) -> bool {
+        true
+    }
+    /// register the listener to notify when the data changed. Trees showing
+    /// the model apply the changes after every event the app handled.
+    fn set_change_listener(&self, _listener: TreeChangeListener<U>) {}
+}
+
+/// A change of the data of a `TreeModel`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeChange<U> {
+    /// The caption, icon, tooltip, CSS class or enabled state of a node changed.
+    Node(U),
+    /// Children of a node have been added, removed or changed.
+    Children(U),
+    /// Anything may have changed, including the roots.
+    All,
+}
+
+/// Listener for changes of a `TreeModel`.
+pub type TreeChangeListener<U> = Rc<dyn Fn(TreeChange<U>)>;
+
+/// A job loading the children of a node, see `TreeModel::children_loader`.
+pub type ChildrenLoader<U> = Box<dyn FnOnce() -> Result<Vec<U>, String> + Send>;
+
+/// Starts a children loader on a worker thread for the node with the given id.
+type LoaderSpawner<U> = Box<dyn Fn(&str, ChildrenLoader<U>)>;
+
+/// Children loaded on a worker thread, sent to the tree through the app handle.
+struct LoadedChildren<U> {
+    tree: String,
+    parent: String,
+    children: Result<Vec<U>, String>,
 }
 
 /// Internal representation of the current tree structure.
 struct TreeNode<U> {
     id: String,
     caption: String,
+    icon: Option<Icon>,
+    tooltip: Option<String>,
+    css_class: Option<String>,
+    enabled: bool,
+    check_state: CheckState,
+    /// the check state shown in the page.
+    check_shown: CheckState,
     nodes: Vec<TreeNode<U>>,
     has_children: bool,
     children_loaded: bool,
+    /// the children are being loaded on a worker thread.
+    loading: bool,
+    expanded: bool,
     user_object: U,
 }
 
 impl<U> TreeNode<U> {
     /// Create a new tree node
-    fn new(caption: impl Into<String>, user_object: U, has_children: bool) -> Self {
+    fn new(user_object: U, has_children: bool) -> Self {
         TreeNode {
             id: create_id(),
-            caption: caption.into(),
+            caption: String::new(),
+            icon: None,
+            tooltip: None,
+            css_class: None,
+            enabled: true,
+            check_state: CheckState::Unchecked,
+            check_shown: CheckState::Unchecked,
             nodes: Vec::new(),
             has_children,
             children_loaded: false,
+            loading: false,
+            expanded: false,
             user_object,
         }
     }
 
     /// render this tree node as HTML.
-    fn render_node(&self) -> String {
-        format!(
-            r#"<li id="{id}" data-caption="{caption}" data-collapsed="true">{children}</li>"#,
-            id = self.id,
-            caption = self.caption,
-            children = if self.has_children { "<ul></ul>" } else { "" },
-        )
+    fn render_node(&self, draggable: bool) -> Node {
+        let mut node = Element::new("li")
+            .id(&self.id)
+            .caption("data-caption", &self.caption)
+            .data("collapsed", true);
+        if draggable {
+            node = node.attr("draggable", "true");
+        }
+        if let Some(icon) = &self.icon {
+            node = node.data("icon", icon.glyph());
+        }
+        if let Some(tooltip) = &self.tooltip {
+            node = node.attr("title", tooltip.as_str());
+        }
+        if let Some(css_class) = &self.css_class {
+            node = node.class(css_class.as_str()).data("css-class", css_class.as_str());
+        }
+        if !self.enabled {
+            node = node.class("disabled");
+        }
+        if self.has_children {
+            node = node.child(Element::new("ul"));
+        }
+        node.into()
     }
 
     /// find a child node recursively by its id.
@@ -67,6 +175,128 @@ impl<U> TreeNode<U> {
         None
     }
 
+    /// collect the ids of this node and its visible descendants in display order.
+    fn collect_visible(&self, ids: &mut Vec<String>) {
+        ids.push(self.id.clone());
+        if self.expanded {
+            for child in &self.nodes {
+                child.collect_visible(ids);
+            }
+        }
+    }
+
+    /// collect the ids of this node and all its descendants.
+    fn collect_ids(&self, ids: &mut Vec<String>) {
+        ids.push(self.id.clone());
+        for child in &self.nodes {
+            child.collect_ids(ids);
+        }
+    }
+
+    /// the icon as JavaScript argument.
+    fn icon_js(&self) -> String {
+        match &self.icon {
+            Some(icon) => format!("'{}'", escape_js_string(&icon.glyph().to_string())),
+            None => "null".into(),
+        }
+    }
+
+    /// the tooltip, CSS class and enabled state as JavaScript arguments.
+    fn style_js(&self) -> String {
+        format!(
+            "{tooltip}, {css_class}, {enabled}",
+            tooltip = js_string(self.tooltip.as_deref()),
+            css_class = js_string(self.css_class.as_deref()),
+            enabled = self.enabled
+        )
+    }
+
+    /// the JavaScript appending this node to a loaded parent node.
+    fn add_js(&self, tree_id: &str, parent_id: &str) -> String {
+        format!(
+            "add_tree_node('{tree}', '{parent}', '{id}', '{caption}', {has_children}, {icon}, {style})",
+            tree = tree_id,
+            parent = parent_id,
+            id = self.id,
+            caption = escape_js_string(&escape_html_text(&self.caption)),
+            has_children = self.has_children,
+            icon = self.icon_js(),
+            style = self.style_js()
+        )
+    }
+
+    /// the JavaScript inserting this node into the page.
+    fn insert_js(&self, tree_id: &str, parent_id: Option<&str>, before_id: Option<&str>) -> String {
+        format!(
+            "insert_tree_node('{tree}', {parent}, {before}, '{id}', '{caption}', {has_children}, {icon}, {style})",
+            tree = tree_id,
+            parent = js_string(parent_id),
+            before = js_string(before_id),
+            id = self.id,
+            caption = escape_js_string(&escape_html_text(&self.caption)),
+            has_children = self.has_children,
+            icon = self.icon_js(),
+            style = self.style_js()
+        )
+    }
+
+    /// the JavaScript showing the current appearance of this node.
+    fn update_js(&self) -> String {
+        format!(
+            "update_tree_node('{id}', '{caption}', {icon}, {style})",
+            id = self.id,
+            caption = escape_js_string(&escape_html_text(&self.caption)),
+            icon = self.icon_js(),
+            style = self.style_js()
+        )
+    }
+
+    /// check or uncheck this node and all its descendants.
+    fn set_checked(&mut self, checked: bool) {
+        self.check_state = if checked { CheckState::Checked } else { CheckState::Unchecked };
+        for child in &mut self.nodes {
+            child.set_checked(checked);
+        }
+    }
+
+    /// collect the topmost checked user objects of this subtree.
+    fn collect_checked(&self, objects: &mut Vec<U>)
+    where
+        U: Clone,
+    {
+        match self.check_state {
+            CheckState::Checked => objects.push(self.user_object.clone()),
+            CheckState::Indeterminate => {
+                for child in &self.nodes {
+                    child.collect_checked(objects);
+                }
+            }
+            CheckState::Unchecked => {}
+        }
+    }
+
+    /// find a node recursively by its user object.
+    fn find_object(&self, object: &U) -> Option<&TreeNode<U>>
+    where
+        U: PartialEq,
+    {
+        if self.user_object == *object {
+            return Some(self);
+        }
+        self.nodes.iter().find_map(|child| child.find_object(object))
+    }
+
+    /// find a mutable node recursively by its user object.
+    fn find_object_mut(&mut self, object: &U) -> Option<&mut TreeNode<U>>
+    where
+        U: PartialEq,
+    {
+        if self.user_object == *object {
+            return Some(self);
+        }
+        self.nodes.iter_mut().find_map(|child| child.find_object_mut(object))
+    }
+
     /// return a mutable child node by its id.
     fn find_node_mut(&mut self, id: &str) -> Option<&mut TreeNode<U>> {
         if self.id == id {
@@ -83,79 +313,921 @@ impl<U> TreeNode<U> {
     }
 }
 
+/// How many nodes of a tree can be selected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SelectionMode {
+    /// A click selects a single node.
+    Single,
+    /// Ctrl+click adds or removes nodes, Shift+click selects a range of nodes.
+    Multiple,
+}
+
+/// The selected nodes of a tree.
+struct Selection {
+    mode: SelectionMode,
+    /// ids of the selected nodes in the order they have been selected.
+    nodes: Vec<String>,
+    /// the node ranges are selected from.
+    anchor: Option<String>,
+    /// the selection as shown in the page.
+    shown: Vec<String>,
+}
+
+type SelectionListener<U> = Box<dyn Fn(&mut dyn Backend, &[U])>;
+
+/// Where a node of a tree has been dropped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DropTarget<U> {
+    /// Another node of the same tree.
+    Node(U),
+    /// The element with the given id outside the tree, usually the root of another
+    /// component. For a node of another tree it is the id of that tree.
+    Element(String),
+}
+
+type DropListener<U> = Box<dyn Fn(&mut dyn Backend, &U, &DropTarget<U>)>;
+
+type CheckListener<U> = Box<dyn Fn(&mut dyn Backend, &[U])>;
+
 /// Tree widget
 #[derive(Clone)]
 pub struct Tree<U> {
     id: String,
     roots: Rc<RefCell<Vec<TreeNode<U>>>>,
-    click_event: Rc<RefCell<Option<Box<dyn Fn(&mut WebView<()>, &U)>>>>,
+    click_event: Rc<RefCell<Option<Box<dyn Fn(&mut dyn Backend, &U)>>>>,
+    selection: Rc<RefCell<Selection>>,
+    selection_event: Rc<RefCell<Option<SelectionListener<U>>>>,
+    drop_event: Rc<RefCell<Option<DropListener<U>>>>,
+    checkable: Rc<Cell<bool>>,
+    editable: Rc<Cell<bool>>,
+    check_event: Rc<RefCell<Option<CheckListener<U>>>>,
+    loader_spawner: Rc<RefCell<Option<LoaderSpawner<U>>>>,
+    model_changes: Rc<RefCell<Vec<TreeChange<U>>>>,
     model: Rc<dyn TreeModel<U>>,
+    dirty: Dirty,
 }
 
 impl<U> Tree<U> {
-    pub fn new(model: impl TreeModel<U> + 'static) -> Self {
+    pub fn new(model: impl TreeModel<U> + 'static) -> Self
+    where
+        U: 'static,
+    {
+        let model_changes: Rc<RefCell<Vec<TreeChange<U>>>> = Default::default();
+        let dirty = Dirty::default();
+        model.set_change_listener(enclose!((model_changes, dirty) Rc::new(move |change| {
+            model_changes.borrow_mut().push(change);
+            dirty.mark();
+        })));
         Tree {
             id: create_id(),
             roots: Rc::new(RefCell::new(Vec::new())),
             click_event: Rc::new(RefCell::new(None)),
+            selection: Rc::new(RefCell::new(Selection {
+                mode: SelectionMode::Single,
+                nodes: Vec::new(),
+                anchor: None,
+                shown: Vec::new(),
+            })),
+            selection_event: Rc::new(RefCell::new(None)),
+            drop_event: Rc::new(RefCell::new(None)),
+            checkable: Rc::new(Cell::new(false)),
+            editable: Rc::new(Cell::new(false)),
+            check_event: Rc::new(RefCell::new(None)),
+            loader_spawner: Rc::new(RefCell::new(None)),
+            model_changes,
             model: Rc::new(model),
+            dirty,
         }
     }
 
-    pub fn set_click_event(&self, event: impl Fn(&mut WebView<()>, &U) + 'static) {
+    pub fn set_click_event(&self, event: impl Fn(&mut dyn Backend, &U) + 'static) {
         self.click_event.borrow_mut().replace(Box::new(event));
     }
 
+    /// Allow selecting a single or multiple nodes. Single by default.
+    pub fn set_selection_mode(&self, mode: SelectionMode) {
+        let mut selection = self.selection.borrow_mut();
+        selection.mode = mode;
+        if mode == SelectionMode::Single && selection.nodes.len() > 1 {
+            let last = selection.nodes.pop();
+            selection.nodes = last.into_iter().collect();
+            self.dirty.mark();
+        }
+    }
+
+    /// Call `listener` with the selected user objects whenever the user changed the selection.
+    pub fn set_selection_changed_event(&self, listener: impl Fn(&mut dyn Backend, &[U]) + 'static) {
+        self.selection_event.borrow_mut().replace(Box::new(listener));
+    }
+
+    /// Let the user drag nodes and call `listener` with the dragged user object
+    /// and its target when it has been dropped.
+    ///
+    /// Nodes can be dropped onto the nodes the model's `can_drop` allows and onto
+    /// other components. The tree does not move the node, the listener changes the
+    /// model and e.g. calls `remove_node` and `insert_child`. Set it before the
+    /// tree is rendered.
+    pub fn set_drop_event(&self, listener: impl Fn(&mut dyn Backend, &U, &DropTarget<U>) + 'static) {
+        self.drop_event.borrow_mut().replace(Box::new(listener));
+    }
+
+    /// Whether `source` can be dropped onto `target`.
+    fn accepts_drop(&self, source: &TreeNode<U>, target: &TreeNode<U>) -> bool {
+        source.find_node(&target.id).is_none() && self.accepts_drop_outside(source, target)
+    }
+
+    /// Whether `source` can be dropped onto `target`, which is none of its descendants.
+    fn accepts_drop_outside(&self, source: &TreeNode<U>, target: &TreeNode<U>) -> bool {
+        target.enabled && self.model.can_drop(&source.user_object, &target.user_object)
+    }
+
+    /// Collect the ids of the nodes `source` can be dropped onto as JavaScript arguments.
+    fn collect_drop_targets(&self, source: &TreeNode<U>, nodes: &[TreeNode<U>], targets: &mut Vec<String>) {
+        for node in nodes {
+            // a node cannot be dropped onto itself or its descendants
+            if node.id == source.id {
+                continue;
+            }
+            if self.accepts_drop_outside(source, node) {
+                targets.push(format!("'{}'", escape_js_string(&node.id)));
+            }
+            self.collect_drop_targets(source, &node.nodes, targets);
+        }
+    }
+
+    /// Tell the page onto which nodes the dragged node can be dropped.
+    fn start_drag(&self, backend: &mut dyn Backend, node_id: &str) {
+        let targets = {
+            let roots = self.roots.borrow();
+            let source = match find_tree_node(&roots, node_id) {
+                Some(source) if source.enabled => source,
+                _ => return,
+            };
+            let mut targets = Vec::new();
+            self.collect_drop_targets(source, &roots, &mut targets);
+            targets
+        };
+        let js = format!("set_tree_drop_targets('{tree}', [{ids}])", tree = self.id, ids = targets.join(", "));
+        if let Err(eval_error) = backend.eval(&js) {
+            warn!(target: "tree", "Could not set drop targets: {}", eval_error);
+        }
+    }
+
+    /// Show a check box in front of every node. Set it before the tree is rendered.
+    ///
+    /// Checking a node checks all its descendants, including the ones not loaded
+    /// yet. A node whose children are partially checked is shown as indeterminate.
+    pub fn set_checkable(&self, checkable: bool) {
+        self.checkable.set(checkable);
+    }
+
+    /// Call `listener` with the checked user objects, as returned by
+    /// `checked_items`, whenever the user checked or unchecked a node.
+    pub fn set_check_changed_event(&self, listener: impl Fn(&mut dyn Backend, &[U]) + 'static) {
+        self.check_event.borrow_mut().replace(Box::new(listener));
+    }
+
+    /// Let the user rename nodes by a double click or F2, using the model's
+    /// `rename`. Set it before the tree is rendered.
+    pub fn set_editable(&self, editable: bool) {
+        self.editable.set(editable);
+    }
+
+    /// Derive the check state of all nodes with loaded children from their
+    /// children and show the changed check states in the page.
+    fn update_checks(&self, backend: &mut dyn Backend) -> Result<(), Box<dyn Error>> {
+        if !self.checkable.get() {
+            return Ok(());
+        }
+        derive_check_states(&mut self.roots.borrow_mut());
+        self.sync_checks(backend)
+    }
+
+    /// Show the check states in the page which changed.
+    fn sync_checks(&self, backend: &mut dyn Backend) -> Result<(), Box<dyn Error>> {
+        let mut changes = Vec::new();
+        collect_check_changes(&mut self.roots.borrow_mut(), &mut changes);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        backend.eval(&format!("set_tree_checks('{tree}', [{checks}])", tree = self.id, checks = changes.join(", ")))
+    }
+
+    /// The check state of a new node below a parent with the given check state.
+    fn inherited_check_state(parent: CheckState) -> CheckState {
+        if parent == CheckState::Checked {
+            CheckState::Checked
+        } else {
+            CheckState::Unchecked
+        }
+    }
+
+    /// Deselect all nodes. A running app shows it after every event it handled.
+    pub fn clear_selection(&self) {
+        let mut selection = self.selection.borrow_mut();
+        selection.nodes.clear();
+        selection.anchor = None;
+        self.dirty.mark();
+    }
+
+    /// The ids of all visible nodes in display order.
+    fn visible_nodes(&self) -> Vec<String> {
+        let mut ids = Vec::new();
+        for root in &*self.roots.borrow() {
+            root.collect_visible(&mut ids);
+        }
+        ids
+    }
+
+    /// The ids of all visible and enabled nodes in display order.
+    fn selectable_nodes(&self) -> Vec<String> {
+        let roots = self.roots.borrow();
+        let mut ids = self.visible_nodes();
+        ids.retain(|id| matches!(find_tree_node(&roots, id), Some(node) if node.enabled));
+        ids
+    }
+
+    /// Change the selection for a click on a node, returning whether it changed.
+    fn select_clicked(&self, node_id: &str, ctrl: bool, shift: bool) -> bool {
+        let visible = if shift { self.selectable_nodes() } else { Vec::new() };
+        let mut selection = self.selection.borrow_mut();
+        let previous = selection.nodes.clone();
+        let multiple = selection.mode == SelectionMode::Multiple;
+        let anchor = selection.anchor.clone();
+        let range = anchor.and_then(|anchor| {
+            let from = visible.iter().position(|id| *id == anchor)?;
+            let to = visible.iter().position(|id| id == node_id)?;
+            Some(visible[from.min(to)..=from.max(to)].to_vec())
+        });
+
+        match range {
+            Some(range) if multiple && shift => selection.nodes = range,
+            _ if multiple && ctrl => {
+                match selection.nodes.iter().position(|id| id == node_id) {
+                    Some(index) => {
+                        selection.nodes.remove(index);
+                    }
+                    None => selection.nodes.push(node_id.into()),
+                }
+                selection.anchor = Some(node_id.into());
+            }
+            _ => {
+                selection.nodes = vec![node_id.into()];
+                selection.anchor = Some(node_id.into());
+            }
+        }
+        selection.nodes != previous
+    }
+
+    /// Forget the selection of removed nodes.
+    fn deselect(&self, removed: &[String]) {
+        let mut selection = self.selection.borrow_mut();
+        selection.nodes.retain(|id| !removed.contains(id));
+        if matches!(&selection.anchor, Some(anchor) if removed.contains(anchor)) {
+            selection.anchor = None;
+        }
+    }
+
+    /// Show the current selection in the page if it changed.
+    fn sync_selection(&self, backend: &mut dyn Backend) -> Result<(), Box<dyn Error>> {
+        let mut selection = self.selection.borrow_mut();
+        if selection.nodes == selection.shown {
+            return Ok(());
+        }
+        let ids: Vec<String> = selection.nodes.iter().map(|id| format!("'{}'", escape_js_string(id))).collect();
+        let focus = match selection.nodes.last() {
+            Some(id) => format!("'{}'", escape_js_string(id)),
+            None => "null".into(),
+        };
+        backend.eval(&format!(
+            "set_tree_selection('{tree}', [{ids}], {focus})",
+            tree = self.id,
+            ids = ids.join(", "),
+            focus = focus
+        ))?;
+        selection.shown = selection.nodes.clone();
+        Ok(())
+    }
+
     fn create_tree_node(&self, node_object: U) -> TreeNode<U> {
-        let caption = self.model.caption(&node_object);
         let has_children = self.model.has_children(&node_object);
-        TreeNode::new(caption, node_object, has_children)
+        let mut node = TreeNode::new(node_object, has_children);
+        self.load_appearance(&mut node);
+        node
+    }
+
+    /// Read caption, icon, tooltip, CSS class and enabled state of a node from
+    /// the model, returning whether any of them changed.
+    fn load_appearance(&self, node: &mut TreeNode<U>) -> bool {
+        let object = &node.user_object;
+        let caption = self.model.caption(object);
+        let icon = self.model.icon(object);
+        let tooltip = self.model.tooltip(object);
+        let css_class = self.model.css_class(object);
+        let enabled = self.model.is_enabled(object);
+        let changed = node.caption != caption
+            || node.icon != icon
+            || node.tooltip != tooltip
+            || node.css_class != css_class
+            || node.enabled != enabled;
+        node.caption = caption;
+        node.icon = icon;
+        node.tooltip = tooltip;
+        node.css_class = css_class;
+        node.enabled = enabled;
+        changed
+    }
+
+    fn render_roots(&mut self) -> Vec<Node> {
+        let roots = self.model.roots().into_iter().map(|root| self.create_tree_node(root)).collect();
+        self.roots.replace(roots);
+
+        let draggable = self.drop_event.borrow().is_some();
+        self.roots.borrow().iter().map(|root| root.render_node(draggable)).collect()
     }
 
-    fn render_roots(&mut self) -> String {
-        for root in self.model.roots() {
-            self.roots.borrow_mut().push(self.create_tree_node(root));
+    fn set_expanded(&self, node_id: &str, expanded: bool) {
+        if let Some(node) = find_tree_node_mut(&mut self.roots.borrow_mut(), node_id) {
+            node.expanded = expanded;
         }
+    }
+
+    fn create_children(&mut self, parent_id: &str, backend: &mut dyn Backend) {
+        let (children, loader) = {
+            let roots = self.roots.borrow();
+            let parent_node = match find_tree_node(&roots, parent_id) {
+                Some(parent_node) if !parent_node.children_loaded && !parent_node.loading => parent_node,
+                _ => return,
+            };
+            match self.model.children_loader(&parent_node.user_object) {
+                Some(loader) => (None, Some(loader)),
+                None => (Some(self.model.children(&parent_node.user_object)), None),
+            }
+        };
 
-        let mut s = String::new();
-        for root in &*self.roots.borrow_mut() {
-            s.push_str(root.render_node().as_str());
+        let clean_js = format!(
+            "clear_node('{tree_id}', '{node_id}')",
+            tree_id = self.id,
+            node_id = parent_id
+        );
+        let _clean_result = backend.eval(clean_js.as_str());
+
+        let loader = match (loader, self.loader_spawner.borrow().as_ref()) {
+            (Some(loader), Some(spawn)) => {
+                spawn(parent_id, loader);
+                None
+            }
+            (loader, _) => loader,
+        };
+        match (children, loader) {
+            (Some(children), _) => self.add_children(backend, parent_id, children),
+            (None, Some(loader)) => self.show_children(backend, parent_id, loader()),
+            (None, None) => {
+                if let Some(parent_node) = find_tree_node_mut(&mut self.roots.borrow_mut(), parent_id) {
+                    parent_node.loading = true;
+                }
+                let js = format!(
+                    "add_tree_loading_node('{tree}', '{parent}', '{parent}-loading', 'Loading\u{2026}')",
+                    tree = self.id,
+                    parent = parent_id
+                );
+                if let Err(eval_error) = backend.eval(&js) {
+                    warn!(target: "tree", "Could not show loading node: {}", eval_error);
+                }
+            }
         }
-        s
     }
 
-    fn create_children(&mut self, parent_id: &str, webview: &mut WebView<()>) {
+    /// Add the nodes of loaded children to a node.
+    fn add_children(&self, backend: &mut dyn Backend, parent_id: &str, children: Vec<U>) {
         let mut roots = self.roots.borrow_mut();
-        if let Some(parent_node) = find_tree_node(roots.as_mut(), parent_id) {
-            if parent_node.children_loaded {
+        let parent_node = match find_tree_node_mut(&mut roots, parent_id) {
+            Some(parent_node) => parent_node,
+            None => return,
+        };
+        let check_state = Self::inherited_check_state(parent_node.check_state);
+        parent_node.children_loaded = true;
+        parent_node.loading = false;
+        for new_child in children {
+            let mut new_node = self.create_tree_node(new_child);
+            new_node.check_state = check_state;
+            let js = new_node.add_js(&self.id, parent_id);
+            if backend.eval(js.as_str()).is_ok() {
+                parent_node.nodes.push(new_node);
+            }
+        }
+    }
+
+    /// Add loaded children to a node or tell the user why they could not be loaded.
+    fn show_children(&self, backend: &mut dyn Backend, parent_id: &str, children: Result<Vec<U>, String>) {
+        let message = match children {
+            Ok(children) => return self.add_children(backend, parent_id, children),
+            Err(message) => message,
+        };
+        if let Some(parent_node) = find_tree_node_mut(&mut self.roots.borrow_mut(), parent_id) {
+            parent_node.loading = false;
+        }
+        let js = format!(
+            "show_tree_load_error('{tree}', '{parent}', '{message}')",
+            tree = self.id,
+            parent = parent_id,
+            message = escape_js_string(&escape_html_text(&message))
+        );
+        if let Err(eval_error) = backend.eval(&js) {
+            warn!(target: "tree", "Could not show loading error: {}", eval_error);
+        }
+    }
+
+    /// Show children loaded on a worker thread.
+    fn receive_children(&self, backend: &mut dyn Backend, parent_id: &str, children: Result<Vec<U>, String>) {
+        // the node may have been removed meanwhile
+        let loading = matches!(find_tree_node(&self.roots.borrow(), parent_id), Some(node) if node.loading);
+        if !loading {
+            return;
+        }
+        let js = format!("remove_tree_node('{tree}', '{parent}-loading')", tree = self.id, parent = parent_id);
+        if let Err(eval_error) = backend.eval(&js) {
+            warn!(target: "tree", "Could not remove loading node: {}", eval_error);
+        }
+        self.show_children(backend, parent_id, children);
+        if let Err(eval_error) = self.update_checks(backend) {
+            warn!(target: "tree", "Could not show check states: {}", eval_error);
+        }
+    }
+}
+
+impl<U: Clone + Send + 'static> Tree<U> {
+    /// Run the loaders returned by the model's `children_loader` on worker threads,
+    /// showing a placeholder node until the children have been loaded. The children
+    /// are delivered through the handle of `app`, `None` loads them right away again.
+    /// Set it before nodes are expanded.
+    pub fn set_background_loading(&self, app: Option<&App>) {
+        let app = match app {
+            Some(app) => app,
+            None => {
+                self.loader_spawner.replace(None);
                 return;
             }
+        };
+        let tree = self.clone();
+        app.subscribe(move |backend, loaded: &LoadedChildren<U>| {
+            if loaded.tree == tree.id {
+                tree.receive_children(backend, &loaded.parent, loaded.children.clone());
+            }
+        });
+        let tree_id = self.id.clone();
+        let handle = app.handle();
+        let spawner: LoaderSpawner<U> = Box::new(move |parent_id, loader| {
+            let tree = tree_id.clone();
+            let parent = parent_id.to_string();
+            let handle = handle.clone();
+            thread::spawn(move || {
+                let children = run_loader(loader);
+                if let Err(app_error) = handle.send(LoadedChildren { tree, parent, children }) {
+                    warn!(target: "tree", "Could not deliver loaded children: {}", app_error);
+                }
+            });
+        });
+        self.loader_spawner.replace(Some(spawner));
+    }
+}
+
+impl<U: Clone> Tree<U> {
+    /// The user objects of the selected nodes in the order they have been selected.
+    pub fn selected(&self) -> Vec<U> {
+        let roots = self.roots.borrow();
+        self.selection
+            .borrow()
+            .nodes
+            .iter()
+            .filter_map(|id| find_tree_node(&roots, id))
+            .map(|node| node.user_object.clone())
+            .collect()
+    }
+
+    /// Handle a click on a node.
+    fn click_node(&self, backend: &mut dyn Backend, node_id: &str, ctrl: bool, shift: bool) {
+        // Metro marks every clicked node as current, so the selection has to be shown again
+        self.selection.borrow_mut().shown.clear();
+        let user_object = match find_tree_node(&self.roots.borrow(), node_id) {
+            Some(node) if node.enabled => Some(node.user_object.clone()),
+            Some(_) => None,
+            None => {
+                warn!(target: "tree" , "Could not find child with ID {}", node_id);
+                None
+            }
+        };
+        let changed = user_object.is_some() && self.select_clicked(node_id, ctrl, shift);
+        if let Err(eval_error) = self.sync_selection(backend) {
+            warn!(target: "tree", "Could not show selection: {}", eval_error);
+        }
+        let user_object = match user_object {
+            Some(user_object) => user_object,
+            None => return,
+        };
+
+        if changed {
+            if let Some(listener) = self.selection_event.borrow().as_ref() {
+                listener(backend, &self.selected());
+            }
+        }
+
+        if let Some(listener) = self.click_event.borrow().as_ref() {
+            listener(backend, &user_object);
+        } else if self.selection_event.borrow().is_none() {
+            warn!(target: "tree" , "No listener for tree with ID {}", self.id);
+        }
+    }
+}
+
+impl<U: Clone> Tree<U> {
+    /// The user objects of the checked nodes in display order. Of a checked
+    /// node only the node itself is returned, not its descendants.
+    pub fn checked_items(&self) -> Vec<U> {
+        let mut objects = Vec::new();
+        for root in self.roots.borrow().iter() {
+            root.collect_checked(&mut objects);
+        }
+        objects
+    }
+
+    /// Handle a click on the check box of a node.
+    fn check_node(&self, backend: &mut dyn Backend, node_id: &str, checked: bool) {
+        match find_tree_node_mut(&mut self.roots.borrow_mut(), node_id) {
+            Some(node) if node.enabled => node.set_checked(checked),
+            Some(_) => {}
+            None => {
+                warn!(target: "tree", "Could not find checked node with ID {}", node_id);
+                return;
+            }
+        }
+        if let Err(eval_error) = self.update_checks(backend) {
+            warn!(target: "tree", "Could not show check states: {}", eval_error);
+        }
+        if let Some(listener) = self.check_event.borrow().as_ref() {
+            listener(backend, &self.checked_items());
+        }
+    }
+
+    /// Rename a node with the name the user entered.
+    fn rename_node(&self, backend: &mut dyn Backend, node_id: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        // the model may change the tree while renaming, so the nodes must not stay borrowed
+        let user_object = match find_tree_node(&self.roots.borrow(), node_id) {
+            Some(node) if node.enabled => node.user_object.clone(),
+            Some(_) => return Ok(()),
+            None => {
+                warn!(target: "tree", "Could not find renamed node with ID {}", node_id);
+                return Ok(());
+            }
+        };
+        match self.model.rename(&user_object, name) {
+            Ok(()) => {
+                let js = match find_tree_node_mut(&mut self.roots.borrow_mut(), node_id) {
+                    Some(node) => {
+                        self.load_appearance(node);
+                        node.update_js()
+                    }
+                    None => return Ok(()),
+                };
+                backend.eval(&js)
+            }
+            Err(message) => backend.eval(&format!(
+                "show_tree_edit_error('{id}', '{message}')",
+                id = node_id,
+                message = escape_js_string(&escape_html_text(&message))
+            )),
+        }
+    }
 
-            let clean_js = format!(
-                "clear_node('{tree_id}', '{node_id}')",
-                tree_id = self.id,
-                node_id = parent_id
-            );
-            let _clean_result = webview.eval(clean_js.as_str());
-
-            let user_object = &parent_node.user_object;
-            let new_children = self.model.children(user_object);
-            for new_child in new_children {
-                let new_node = self.create_tree_node(new_child);
-                let js = format!("add_tree_node('{id_tree}', '{id_parent}', '{id_node}', '{caption}', {has_children})",
-                                 id_tree=self.id,
-                                 id_parent=parent_id,
-                                 id_node=new_node.id,
-                                 caption=new_node.caption,
-                                 has_children=new_node.has_children);
-                let result = webview.eval(js.as_str());
-                if result.is_ok() {
-                    let mut_child = find_tree_node_mut(roots.as_mut(), parent_id).unwrap();
-                    mut_child.children_loaded = true;
-                    mut_child.nodes.push(new_node);
+    /// Handle a node dropped onto a node of this tree or another element.
+    fn drop_node(&self, backend: &mut dyn Backend, node_id: &str, target_id: &str) {
+        let (source, target) = {
+            let roots = self.roots.borrow();
+            let source = match find_tree_node(&roots, node_id) {
+                Some(source) => source,
+                None => {
+                    warn!(target: "tree", "Could not find dragged node with ID {}", node_id);
+                    return;
                 }
+            };
+            let target = match find_tree_node(&roots, target_id) {
+                Some(target) if self.accepts_drop(source, target) => DropTarget::Node(target.user_object.clone()),
+                Some(_) => return,
+                None => DropTarget::Element(target_id.into()),
+            };
+            (source.user_object.clone(), target)
+        };
+        if let Some(listener) = self.drop_event.borrow().as_ref() {
+            listener(backend, &source, &target);
+        }
+    }
+}
+
+impl<U: PartialEq> Tree<U> {
+    /// Select the node of a user object, in addition to the selected nodes in
+    /// multiple selection mode. Only nodes loaded already can be selected.
+    ///
+    /// Returns whether an enabled node has been found. A running app shows the selection
+    /// after every event it handled.
+    pub fn select(&self, object: &U) -> bool {
+        let roots = self.roots.borrow();
+        let node = match roots.iter().find_map(|root| root.find_object(object)) {
+            Some(node) if node.enabled => node,
+            _ => return false,
+        };
+        let mut selection = self.selection.borrow_mut();
+        if selection.mode == SelectionMode::Single {
+            selection.nodes.clear();
+        }
+        if !selection.nodes.contains(&node.id) {
+            selection.nodes.push(node.id.clone());
+        }
+        selection.anchor = Some(node.id.clone());
+        self.dirty.mark();
+        true
+    }
+
+    /// The check state of the node of a user object, if it has been loaded.
+    pub fn check_state(&self, object: &U) -> Option<CheckState> {
+        let roots = self.roots.borrow();
+        roots.iter().find_map(|root| root.find_object(object)).map(|node| node.check_state)
+    }
+
+    /// Check or uncheck the node of a user object and all its descendants.
+    /// Returns whether the node has been found. A running app shows it after
+    /// every event it handled.
+    pub fn set_checked(&self, object: &U, checked: bool) -> bool {
+        {
+            let mut roots = self.roots.borrow_mut();
+            match roots.iter_mut().find_map(|root| root.find_object_mut(object)) {
+                Some(node) => node.set_checked(checked),
+                None => return false,
             }
         }
+        derive_check_states(&mut self.roots.borrow_mut());
+        self.dirty.mark();
+        true
+    }
+}
+
+impl<U: PartialEq> Tree<U> {
+    /// Load the roots and all loaded children from the model again, keeping
+    /// nodes which are still there with their state.
+    pub fn refresh_all(&self, backend: &mut dyn Backend) -> Result<(), Box<dyn Error>> {
+        self.refresh_children(backend, None)?;
+        self.update_checks(backend)
+    }
+
+    /// Show the current appearance of a node and load its children from
+    /// the model again, if they have been loaded. Returns whether the node has been found.
+    pub fn refresh_node(&self, backend: &mut dyn Backend, object: &U) -> Result<bool, Box<dyn Error>> {
+        if !self.update_caption(backend, object)? {
+            return Ok(false);
+        }
+        let id = self.find_node_id(object);
+        self.refresh_children(backend, id)?;
+        self.update_checks(backend)?;
+        Ok(true)
+    }
+
+    /// Show the current caption, icon, tooltip, CSS class and enabled state of a
+    /// node as returned by the model.
+    /// Returns whether the node has been found.
+    pub fn update_caption(&self, backend: &mut dyn Backend, object: &U) -> Result<bool, Box<dyn Error>> {
+        let js = {
+            let mut roots = self.roots.borrow_mut();
+            let node = match roots.iter_mut().find_map(|root| root.find_object_mut(object)) {
+                Some(node) => node,
+                None => return Ok(false),
+            };
+            self.load_appearance(node);
+            node.update_js()
+        };
+        backend.eval(&js)?;
+        Ok(true)
+    }
+
+    /// Insert a node for `child` at `index` below `parent`, or below the roots for `None`.
+    ///
+    /// The model has to return the child as well, as children not loaded yet are
+    /// taken from it. Returns whether the parent has been found.
+    pub fn insert_child(&self, backend: &mut dyn Backend, parent: Option<&U>, index: usize, child: U) -> Result<bool, Box<dyn Error>> {
+        let js = {
+            let mut roots = self.roots.borrow_mut();
+            let (parent_id, check_state, list) = match parent {
+                None => (None, CheckState::Unchecked, &mut *roots),
+                Some(parent) => match roots.iter_mut().find_map(|root| root.find_object_mut(parent)) {
+                    Some(node) if !node.children_loaded => {
+                        if node.has_children {
+                            return Ok(true);
+                        }
+                        node.has_children = true;
+                        let js = format!("set_tree_node_expandable('{tree}', '{id}')", tree = self.id, id = node.id);
+                        drop(roots);
+                        backend.eval(&js)?;
+                        return Ok(true);
+                    }
+                    Some(node) => {
+                        let check_state = Self::inherited_check_state(node.check_state);
+                        (Some(node.id.clone()), check_state, &mut node.nodes)
+                    }
+                    None => return Ok(false),
+                },
+            };
+            let index = index.min(list.len());
+            let mut node = self.create_tree_node(child);
+            node.check_state = check_state;
+            let before = list.get(index).map(|sibling| sibling.id.clone());
+            let js = node.insert_js(&self.id, parent_id.as_deref(), before.as_deref());
+            list.insert(index, node);
+            js
+        };
+        backend.eval(&js)?;
+        self.update_checks(backend)?;
+        Ok(true)
+    }
+
+    /// Remove the node of a user object with all its children. Returns whether
+    /// the node has been found.
+    pub fn remove_node(&self, backend: &mut dyn Backend, object: &U) -> Result<bool, Box<dyn Error>> {
+        let removed = match remove_object(&mut self.roots.borrow_mut(), object) {
+            Some(node) => node,
+            None => return Ok(false),
+        };
+        let mut ids = Vec::new();
+        removed.collect_ids(&mut ids);
+        self.deselect(&ids);
+        backend.eval(&format!("remove_tree_node('{tree}', '{id}')", tree = self.id, id = removed.id))?;
+        self.update_checks(backend)?;
+        Ok(true)
+    }
+
+    fn find_node_id(&self, object: &U) -> Option<String> {
+        self.roots.borrow().iter().find_map(|root| root.find_object(object)).map(|node| node.id.clone())
+    }
+
+    /// Load the children of a node (or the roots for `None`) again, recursively
+    /// for all children loaded already.
+    ///
+    /// Nodes still returned by the model are kept with their state and moved
+    /// in the page to the position the model returns them at.
+    fn refresh_children(&self, backend: &mut dyn Backend, parent_id: Option<String>) -> Result<(), Box<dyn Error>> {
+        let objects = match &parent_id {
+            None => self.model.roots(),
+            Some(id) => match find_tree_node(&self.roots.borrow(), id) {
+                Some(node) if node.children_loaded => self.model.children(&node.user_object),
+                _ => return Ok(()),
+            },
+        };
+
+        let mut scripts = Vec::new();
+        let mut removed = Vec::new();
+        let mut loaded = Vec::new();
+        {
+            let mut roots = self.roots.borrow_mut();
+            let (list, check_state) = match &parent_id {
+                None => (&mut *roots, CheckState::Unchecked),
+                Some(id) => match find_tree_node_mut(&mut roots, id) {
+                    Some(node) => (&mut node.nodes, Self::inherited_check_state(node.check_state)),
+                    None => return Ok(()),
+                },
+            };
+
+            let mut old = std::mem::take(list);
+            let old_order: Vec<String> = old.iter().map(|node| node.id.clone()).collect();
+            let mut nodes = Vec::new();
+            let mut inserted = Vec::new();
+            for object in objects {
+                let has_children = self.model.has_children(&object);
+                match old.iter().position(|node| node.user_object == object && node.has_children == has_children) {
+                    Some(index) => {
+                        let mut node = old.remove(index);
+                        if self.load_appearance(&mut node) {
+                            scripts.push(node.update_js());
+                        }
+                        if node.children_loaded {
+                            loaded.push(node.id.clone());
+                        }
+                        nodes.push(node);
+                        inserted.push(false);
+                    }
+                    None => {
+                        let mut node = self.create_tree_node(object);
+                        node.check_state = check_state;
+                        nodes.push(node);
+                        inserted.push(true);
+                    }
+                }
+            }
+
+            let mut removals = Vec::new();
+            for node in old {
+                removals.push(format!("remove_tree_node('{tree}', '{id}')", tree = self.id, id = node.id));
+                node.collect_ids(&mut removed);
+            }
+            let kept: Vec<&String> = nodes.iter().zip(&inserted).filter(|(_, inserted)| !**inserted).map(|(node, _)| &node.id).collect();
+            let reordered = !old_order.iter().filter(|id| !removed.contains(id)).eq(kept);
+            // insert and move from the end, so the following sibling is always in place
+            let mut insertions = Vec::new();
+            for index in (0..nodes.len()).rev() {
+                let before = nodes.get(index + 1).map(|sibling| sibling.id.as_str());
+                if inserted[index] {
+                    insertions.push(nodes[index].insert_js(&self.id, parent_id.as_deref(), before));
+                } else if reordered {
+                    insertions.push(format!("move_tree_node('{id}', {before})", id = nodes[index].id, before = js_string(before)));
+                }
+            }
+            *list = nodes;
+            scripts = removals.into_iter().chain(scripts).chain(insertions).collect();
+        }
+
+        self.deselect(&removed);
+        for js in scripts {
+            backend.eval(&js)?;
+        }
+        for id in loaded {
+            self.refresh_children(backend, Some(id))?;
+        }
+        Ok(())
+    }
+
+    /// Apply the changes the model reported.
+    fn apply_model_changes(&self, backend: &mut dyn Backend) -> Result<(), Box<dyn Error>> {
+        let changes = std::mem::take(&mut *self.model_changes.borrow_mut());
+        for change in changes {
+            match change {
+                TreeChange::Node(object) => {
+                    self.update_caption(backend, &object)?;
+                }
+                TreeChange::Children(object) => {
+                    self.refresh_node(backend, &object)?;
+                }
+                TreeChange::All => self.refresh_all(backend)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// derive the check state of the nodes with loaded children from their children.
+fn derive_check_states<U>(nodes: &mut [TreeNode<U>]) {
+    for node in nodes {
+        if !node.children_loaded || node.nodes.is_empty() {
+            continue;
+        }
+        derive_check_states(&mut node.nodes);
+        let state = node.nodes[0].check_state;
+        node.check_state = if node.nodes.iter().all(|child| child.check_state == state) {
+            state
+        } else {
+            CheckState::Indeterminate
+        };
+    }
+}
+
+/// collect the check states not shown in the page yet as JavaScript arguments.
+fn collect_check_changes<U>(nodes: &mut [TreeNode<U>], changes: &mut Vec<String>) {
+    for node in nodes {
+        if node.check_state != node.check_shown {
+            let state = match node.check_state {
+                CheckState::Unchecked => "unchecked",
+                CheckState::Checked => "checked",
+                CheckState::Indeterminate => "indeterminate",
+            };
+            changes.push(format!("['{}', '{}']", node.id, state));
+            node.check_shown = node.check_state;
+        }
+        collect_check_changes(&mut node.nodes, changes);
+    }
+}
+
+/// remove the node of a user object from a list of nodes or their descendants.
+fn remove_object<U: PartialEq>(nodes: &mut Vec<TreeNode<U>>, object: &U) -> Option<TreeNode<U>> {
+    if let Some(index) = nodes.iter().position(|node| node.user_object == *object) {
+        return Some(nodes.remove(index));
+    }
+    nodes.iter_mut().find_map(|node| remove_object(&mut node.nodes, object))
+}
+
+/// run a children loader, turning a panic into an error so the node does not keep loading forever.
+fn run_loader<U>(loader: ChildrenLoader<U>) -> Result<Vec<U>, String> {
+    panic::catch_unwind(AssertUnwindSafe(loader)).unwrap_or_else(|panic| Err(panic_message(&*panic)))
+}
+
+/// the message a panic has been started with.
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+        (Some(message), _) => message.to_string(),
+        (_, Some(message)) => message.clone(),
+        _ => "Loading the children failed".into(),
+    }
+}
+
+/// an optional string as JavaScript argument.
+fn js_string(value: Option<&str>) -> String {
+    match value {
+        Some(value) => format!("'{}'", escape_js_string(value)),
+        None => "null".into(),
     }
 }
 
@@ -182,33 +1254,70 @@ fn find_tree_node_mut<'a, U>(roots: &'a mut Vec<TreeNode<U>>, id: &str) -> Optio
     None
 }
 
-impl<U: Clone> Component for Tree<U> {
-    fn render(&mut self) -> String {
+impl<U: Clone + PartialEq + 'static> Component for Tree<U> {
+    fn render(&mut self) -> Node {
         let roots = self.render_roots();
-        format!(
-            r#"<ul id="{id}" data-role="treeview" data-on-expand-node="fire_node_expand" data-on-tree-view-create="fire_created" data-on-node-click="fire_node_clicked">{roots}</ul>"#,
-            id = self.id(),
-            roots = roots,
-        )
+        self.dirty.take();
+        {
+            // the nodes have been created anew
+            let mut selection = self.selection.borrow_mut();
+            selection.nodes.clear();
+            selection.anchor = None;
+            selection.shown.clear();
+        }
+        let mut tree = Element::new("ul")
+            .id(self.id())
+            .attr("tabindex", 0)
+            .data("role", "treeview")
+            .data("on-expand-node", "fire_node_expand")
+            .data("on-collapse-node", "fire_node_collapse")
+            .data("on-tree-view-create", "tree_created")
+            .data("on-node-click", "fire_node_clicked")
+            .on("keydown", format!("tree_key_down(event, '{id}')", id = self.id));
+        if self.checkable.get() {
+            tree = tree.data("checkable", true);
+        }
+        if self.editable.get() {
+            tree = tree.data("editable", true);
+        }
+        if self.drop_event.borrow().is_some() {
+            tree = tree
+                .data("draggable", true)
+                .on("dragstart", format!("tree_drag_start(event, '{id}')", id = self.id));
+        }
+        tree.on_event(event_handler(self))
+            .on_update(enclose!((self => tree) Rc::new(move |backend| {
+                if !tree.dirty.take() {
+                    return Ok(());
+                }
+                tree.apply_model_changes(backend)?;
+                tree.sync_checks(backend)?;
+                tree.sync_selection(backend)
+            })))
+            .children(roots)
+            .into()
     }
 
-    fn handle_event(&mut self, webview: &mut WebView<()>, event: &Event) {
+    fn handle_event(&mut self, backend: &mut dyn Backend, event: &Event) {
         if event.id == self.id {
             match &event.value {
-                ChildClicked(child_id) => {
-                    if let Some(listener) = self.click_event.borrow_mut().as_ref() {
-                        let roots = self.roots.borrow_mut();
-                        if let Some(child) = find_tree_node(roots.as_ref(), child_id) {
-                            listener(webview, &child.user_object);
-                        } else {
-                            warn!(target: "tree" , "Could not find child with ID {}", child_id);
-                        }
-                    } else {
-                        warn!(target: "tree" , "No listener for tree with ID {}", self.id);
+                ChildClicked(child_id) => self.click_node(backend, child_id, false, false),
+                NodeClicked { node, ctrl, shift } => self.click_node(backend, node, *ctrl, *shift),
+                NodeExpand(child_id) => {
+                    self.create_children(child_id, backend);
+                    self.set_expanded(child_id, true);
+                    if let Err(eval_error) = self.update_checks(backend) {
+                        warn!(target: "tree", "Could not show check states: {}", eval_error);
                     }
                 }
-                NodeExpand(child_id) => {
-                    self.create_children(child_id, webview);
+                NodeCollapse(child_id) => self.set_expanded(child_id, false),
+                NodeDragStart(node_id) => self.start_drag(backend, node_id),
+                NodeDropped { node, target } => self.drop_node(backend, node, target),
+                NodeChecked { node, checked } => self.check_node(backend, node, *checked),
+                NodeRenamed { node, name } => {
+                    if let Err(eval_error) = self.rename_node(backend, node, name) {
+                        warn!(target: "tree", "Could not show renamed node: {}", eval_error);
+                    }
                 }
                 _ => {}
             }
@@ -219,3 +1328,522 @@ impl<U: Clone> Component for Tree<U> {
         self.id.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::RecordingBackend;
+    use crate::testing::TestApp;
+    use std::time::Duration;
+
+    struct NumberTree {}
+
+    impl TreeModel<u32> for NumberTree {
+        fn roots(&self) -> Vec<u32> {
+            vec![1]
+        }
+
+        fn children(&self, parent: &u32) -> Vec<u32> {
+            vec![parent * 10]
+        }
+
+        fn caption(&self, node: &u32) -> String {
+            format!("Node {}", node)
+        }
+
+        fn has_children(&self, node: &u32) -> bool {
+            *node < 10
+        }
+    }
+
+    #[test]
+    fn test_expand_node() {
+        let mut tree = Tree::new(NumberTree {});
+        tree.render();
+        let root_id = tree.roots.borrow()[0].id.clone();
+
+        let mut backend = RecordingBackend::new();
+        tree.handle_event(&mut backend, &Event {
+            id: tree.id(),
+            value: NodeExpand(root_id.clone()),
+        });
+
+        let child_id = tree.roots.borrow()[0].nodes[0].id.clone();
+        assert_eq!(backend.scripts(), [
+            format!("clear_node('{}', '{}')", tree.id(), root_id),
+            format!("add_tree_node('{}', '{}', '{}', 'Node 10', false, null, null, null, true)", tree.id(), root_id, child_id),
+        ]);
+
+        // children are only loaded once
+        tree.handle_event(&mut backend, &Event {
+            id: tree.id(),
+            value: NodeExpand(root_id),
+        });
+        assert_eq!(backend.scripts().len(), 2);
+    }
+
+    struct QuoteTree {}
+
+    impl TreeModel<&'static str> for QuoteTree {
+        fn roots(&self) -> Vec<&'static str> {
+            vec!["<home>"]
+        }
+
+        fn children(&self, _parent: &&'static str) -> Vec<&'static str> {
+            vec!["O'Brien.txt"]
+        }
+
+        fn caption(&self, node: &&'static str) -> String {
+            node.to_string()
+        }
+
+        fn has_children(&self, node: &&'static str) -> bool {
+            node.starts_with('<')
+        }
+
+        fn icon(&self, node: &&'static str) -> Option<Icon> {
+            Some(Icon::for_file(node))
+        }
+    }
+
+    #[test]
+    fn test_escape_captions() {
+        let mut tree = Tree::new(QuoteTree {});
+        let html = tree.render().to_string();
+        assert!(html.contains(r#"data-caption="&amp;lt;home&amp;gt;""#));
+
+        let root_id = tree.roots.borrow()[0].id.clone();
+        let mut backend = RecordingBackend::new();
+        tree.handle_event(&mut backend, &Event {
+            id: tree.id(),
+            value: NodeExpand(root_id),
+        });
+        assert!(backend.scripts()[1].contains(r"'O\'Brien.txt'"));
+        assert!(backend.scripts()[1].ends_with(r#"'\x3Cspan class=\"mif-file-text\"\x3E\x3C/span\x3E', null, null, true)"#));
+    }
+
+    struct ListTree {}
+
+    impl TreeModel<u32> for ListTree {
+        fn roots(&self) -> Vec<u32> {
+            vec![1, 2, 3]
+        }
+
+        fn children(&self, parent: &u32) -> Vec<u32> {
+            vec![parent * 10, parent * 10 + 1]
+        }
+
+        fn caption(&self, node: &u32) -> String {
+            node.to_string()
+        }
+
+        fn has_children(&self, node: &u32) -> bool {
+            *node < 10
+        }
+
+        fn can_drop(&self, _source: &u32, target: &u32) -> bool {
+            *target < 10
+        }
+    }
+
+    fn node_id(tree: &Tree<u32>, object: u32) -> String {
+        tree.roots.borrow().iter().find_map(|root| root.find_object(&object)).unwrap().id.clone()
+    }
+
+    fn click(tree: &mut Tree<u32>, backend: &mut RecordingBackend, object: u32, ctrl: bool, shift: bool) {
+        let node = node_id(tree, object);
+        tree.handle_event(backend, &Event { id: tree.id(), value: NodeClicked { node, ctrl, shift } });
+    }
+
+    #[test]
+    fn test_single_selection() {
+        let mut tree = Tree::new(ListTree {});
+        tree.render();
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        tree.set_selection_changed_event(enclose!((changes) move |_backend, selected: &[u32]| {
+            changes.borrow_mut().push(selected.to_vec())
+        }));
+
+        let mut backend = RecordingBackend::new();
+        click(&mut tree, &mut backend, 1, true, false);
+        click(&mut tree, &mut backend, 2, false, true);
+        click(&mut tree, &mut backend, 2, false, false);
+        assert_eq!(*changes.borrow(), [vec![1], vec![2]]);
+        // shown again after every click, as Metro highlights the clicked node
+        assert_eq!(backend.take_scripts(), [
+            format!("set_tree_selection('{}', ['{}'], '{}')", tree.id(), node_id(&tree, 1), node_id(&tree, 1)),
+            format!("set_tree_selection('{}', ['{}'], '{}')", tree.id(), node_id(&tree, 2), node_id(&tree, 2)),
+            format!("set_tree_selection('{}', ['{}'], '{}')", tree.id(), node_id(&tree, 2), node_id(&tree, 2)),
+        ]);
+
+        assert!(tree.select(&3));
+        assert!(!tree.select(&10));
+        assert_eq!(tree.selected(), [3]);
+        tree.sync_selection(&mut backend).unwrap();
+        assert_eq!(backend.scripts().len(), 1);
+
+        tree.clear_selection();
+        assert!(tree.selected().is_empty());
+    }
+
+    #[test]
+    fn test_multiple_selection() {
+        let mut tree = Tree::new(ListTree {});
+        tree.set_selection_mode(SelectionMode::Multiple);
+        tree.render();
+        let mut backend = RecordingBackend::new();
+        let root_id = node_id(&tree, 1);
+        tree.handle_event(&mut backend, &Event { id: tree.id(), value: NodeExpand(root_id) });
+
+        click(&mut tree, &mut backend, 10, false, false);
+        click(&mut tree, &mut backend, 2, false, true);
+        assert_eq!(tree.selected(), [10, 11, 2]);
+
+        click(&mut tree, &mut backend, 11, true, false);
+        click(&mut tree, &mut backend, 3, true, false);
+        assert_eq!(tree.selected(), [10, 2, 3]);
+
+        // collapsed nodes are not part of ranges
+        let root_id = node_id(&tree, 1);
+        tree.handle_event(&mut backend, &Event { id: tree.id(), value: NodeCollapse(root_id) });
+        click(&mut tree, &mut backend, 1, false, false);
+        click(&mut tree, &mut backend, 2, false, true);
+        assert_eq!(tree.selected(), [1, 2]);
+
+        tree.set_selection_mode(SelectionMode::Single);
+        assert_eq!(tree.selected(), [2]);
+    }
+
+    struct EditableTree {
+        children: Rc<RefCell<Vec<u32>>>,
+        listener: Rc<RefCell<Option<TreeChangeListener<u32>>>>,
+    }
+
+    impl TreeModel<u32> for EditableTree {
+        fn roots(&self) -> Vec<u32> {
+            vec![1]
+        }
+
+        fn children(&self, _parent: &u32) -> Vec<u32> {
+            self.children.borrow().clone()
+        }
+
+        fn caption(&self, node: &u32) -> String {
+            format!("Node {}", node)
+        }
+
+        fn has_children(&self, node: &u32) -> bool {
+            *node == 1
+        }
+
+        fn set_change_listener(&self, listener: TreeChangeListener<u32>) {
+            *self.listener.borrow_mut() = Some(listener);
+        }
+    }
+
+    #[test]
+    fn test_refresh() {
+        let children = Rc::new(RefCell::new(vec![10, 11, 12]));
+        let mut tree = Tree::new(EditableTree { children: children.clone(), listener: Default::default() });
+        tree.render();
+        let mut backend = RecordingBackend::new();
+        let root_id = node_id(&tree, 1);
+        tree.handle_event(&mut backend, &Event { id: tree.id(), value: NodeExpand(root_id.clone()) });
+        click(&mut tree, &mut backend, 11, false, false);
+        backend.take_scripts();
+
+        let removed_id = node_id(&tree, 11);
+        let before_id = node_id(&tree, 12);
+        *children.borrow_mut() = vec![10, 13, 12];
+        assert!(tree.refresh_node(&mut backend, &1).unwrap());
+        assert!(tree.selected().is_empty());
+        assert_eq!(backend.take_scripts(), [
+            format!("update_tree_node('{}', 'Node 1', null, null, null, true)", root_id),
+            format!("remove_tree_node('{}', '{}')", tree.id(), removed_id),
+            format!("insert_tree_node('{}', '{}', '{}', '{}', 'Node 13', false, null, null, null, true)",
+                    tree.id(), root_id, before_id, node_id(&tree, 13)),
+        ]);
+
+        assert!(tree.insert_child(&mut backend, Some(&1), 9, 14).unwrap());
+        assert!(tree.remove_node(&mut backend, &10).unwrap());
+        assert!(!tree.remove_node(&mut backend, &10).unwrap());
+        let visible: Vec<String> = [1, 13, 12, 14].iter().map(|object| node_id(&tree, *object)).collect();
+        assert_eq!(tree.visible_nodes(), visible);
+        assert_eq!(backend.take_scripts()[0],
+                   format!("insert_tree_node('{}', '{}', null, '{}', 'Node 14', false, null, null, null, true)",
+                           tree.id(), root_id, node_id(&tree, 14)));
+
+        *children.borrow_mut() = vec![14, 12, 13];
+        assert!(tree.refresh_node(&mut backend, &1).unwrap());
+        let (id_12, id_13, id_14) = (node_id(&tree, 12), node_id(&tree, 13), node_id(&tree, 14));
+        assert_eq!(backend.take_scripts(), [
+            format!("update_tree_node('{}', 'Node 1', null, null, null, true)", root_id),
+            format!("move_tree_node('{}', null)", id_13),
+            format!("move_tree_node('{}', '{}')", id_12, id_13),
+            format!("move_tree_node('{}', '{}')", id_14, id_12),
+        ]);
+    }
+
+    #[test]
+    fn test_model_changes() {
+        let children = Rc::new(RefCell::new(vec![10]));
+        let listener = Rc::new(RefCell::new(None));
+        let mut tree = Tree::new(EditableTree { children: children.clone(), listener: listener.clone() });
+        tree.render();
+        let mut backend = RecordingBackend::new();
+        let root_id = node_id(&tree, 1);
+        tree.handle_event(&mut backend, &Event { id: tree.id(), value: NodeExpand(root_id) });
+        backend.take_scripts();
+
+        children.borrow_mut().push(11);
+        let notify: TreeChangeListener<u32> = listener.borrow().clone().unwrap();
+        notify(TreeChange::Children(1));
+        tree.apply_model_changes(&mut backend).unwrap();
+        assert!(tree.model_changes.borrow().is_empty());
+        let visible: Vec<String> = [1, 10, 11].iter().map(|object| node_id(&tree, *object)).collect();
+        assert_eq!(tree.visible_nodes(), visible);
+    }
+
+    struct StyledTree {}
+
+    impl TreeModel<u32> for StyledTree {
+        fn roots(&self) -> Vec<u32> {
+            vec![1, 2, 3]
+        }
+
+        fn children(&self, _parent: &u32) -> Vec<u32> {
+            Vec::new()
+        }
+
+        fn caption(&self, node: &u32) -> String {
+            format!("Node {}", node)
+        }
+
+        fn has_children(&self, _node: &u32) -> bool {
+            false
+        }
+
+        fn tooltip(&self, node: &u32) -> Option<String> {
+            Some(format!("Tooltip {}", node))
+        }
+
+        fn css_class(&self, node: &u32) -> Option<String> {
+            if *node == 1 { Some("fg-red".into()) } else { None }
+        }
+
+        fn is_enabled(&self, node: &u32) -> bool {
+            *node != 2
+        }
+    }
+
+    #[test]
+    fn test_node_style() {
+        let mut tree = Tree::new(StyledTree {});
+        tree.set_selection_mode(SelectionMode::Multiple);
+        let html = tree.render().to_string();
+        assert!(html.contains(&format!(
+            r#"<li id="{}" data-caption="Node 1" data-collapsed="true" title="Tooltip 1" class="fg-red" data-css-class="fg-red">"#,
+            node_id(&tree, 1)
+        )));
+        assert!(html.contains(r#"title="Tooltip 2" class="disabled">"#));
+
+        // disabled nodes are neither selected by clicks nor part of ranges
+        let mut backend = RecordingBackend::new();
+        click(&mut tree, &mut backend, 2, false, false);
+        assert!(tree.selected().is_empty());
+        click(&mut tree, &mut backend, 1, false, false);
+        click(&mut tree, &mut backend, 3, false, true);
+        assert_eq!(tree.selected(), [1, 3]);
+        assert!(!tree.select(&2));
+    }
+
+    #[test]
+    fn test_drag_and_drop() {
+        let mut tree = Tree::new(ListTree {});
+        let drops = Rc::new(RefCell::new(Vec::new()));
+        tree.set_drop_event(enclose!((drops) move |_backend, source: &u32, target: &DropTarget<u32>| {
+            drops.borrow_mut().push((*source, target.clone()))
+        }));
+        let html = tree.render().to_string();
+        assert!(html.contains(&format!(r#"<li id="{}" data-caption="1" data-collapsed="true" draggable="true">"#, node_id(&tree, 1))));
+
+        let mut backend = RecordingBackend::new();
+        tree.handle_event(&mut backend, &Event { id: tree.id(), value: NodeExpand(node_id(&tree, 1)) });
+        backend.take_scripts();
+
+        // neither the node itself, its children nor nodes rejected by the model are targets
+        tree.handle_event(&mut backend, &Event { id: tree.id(), value: NodeDragStart(node_id(&tree, 1)) });
+        assert_eq!(backend.take_scripts(), [
+            format!("set_tree_drop_targets('{}', ['{}', '{}'])", tree.id(), node_id(&tree, 2), node_id(&tree, 3)),
+        ]);
+
+        let targets = [(1, node_id(&tree, 10)), (10, node_id(&tree, 11)), (10, node_id(&tree, 2)), (10, "form".into())];
+        for (source, target) in targets.iter().cloned() {
+            let value = NodeDropped { node: node_id(&tree, source), target };
+            tree.handle_event(&mut backend, &Event { id: tree.id(), value });
+        }
+        assert_eq!(*drops.borrow(), [(10, DropTarget::Node(2)), (10, DropTarget::Element("form".into()))]);
+    }
+
+    #[test]
+    fn test_check_nodes() {
+        let mut tree = Tree::new(ListTree {});
+        tree.set_checkable(true);
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        tree.set_check_changed_event(enclose!((changes) move |_backend, checked: &[u32]| {
+            changes.borrow_mut().push(checked.to_vec())
+        }));
+        assert!(tree.render().to_string().contains(r#"data-checkable="true""#));
+
+        let mut backend = RecordingBackend::new();
+        let check = |tree: &mut Tree<u32>, backend: &mut RecordingBackend, object: u32, checked: bool| {
+            let node = node_id(tree, object);
+            tree.handle_event(backend, &Event { id: tree.id(), value: NodeChecked { node, checked } });
+        };
+        tree.handle_event(&mut backend, &Event { id: tree.id(), value: NodeExpand(node_id(&tree, 1)) });
+        backend.take_scripts();
+
+        check(&mut tree, &mut backend, 10, true);
+        assert_eq!(tree.check_state(&1), Some(CheckState::Indeterminate));
+        assert_eq!(backend.take_scripts(), [format!(
+            "set_tree_checks('{}', [['{}', 'indeterminate'], ['{}', 'checked']])",
+            tree.id(), node_id(&tree, 1), node_id(&tree, 10)
+        )]);
+        check(&mut tree, &mut backend, 11, true);
+        assert_eq!(tree.check_state(&1), Some(CheckState::Checked));
+
+        // children loaded later inherit the state of their parent
+        check(&mut tree, &mut backend, 2, true);
+        tree.handle_event(&mut backend, &Event { id: tree.id(), value: NodeExpand(node_id(&tree, 2)) });
+        assert_eq!(tree.check_state(&21), Some(CheckState::Checked));
+        check(&mut tree, &mut backend, 21, false);
+        assert_eq!(*changes.borrow(), [vec![10], vec![1], vec![1, 2], vec![1, 20]]);
+
+        assert!(tree.set_checked(&1, false));
+        assert_eq!(tree.checked_items(), [20]);
+        assert_eq!(tree.check_state(&10), Some(CheckState::Unchecked));
+    }
+
+    struct NamedTree {
+        names: RefCell<Vec<String>>,
+    }
+
+    impl TreeModel<usize> for NamedTree {
+        fn roots(&self) -> Vec<usize> {
+            (0..self.names.borrow().len()).collect()
+        }
+
+        fn children(&self, _parent: &usize) -> Vec<usize> {
+            Vec::new()
+        }
+
+        fn caption(&self, node: &usize) -> String {
+            self.names.borrow()[*node].clone()
+        }
+
+        fn has_children(&self, _node: &usize) -> bool {
+            false
+        }
+
+        fn rename(&self, node: &usize, name: &str) -> Result<(), String> {
+            if name.is_empty() {
+                return Err("<empty>".into());
+            }
+            self.names.borrow_mut()[*node] = name.into();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut tree = Tree::new(NamedTree { names: RefCell::new(vec!["a".into()]) });
+        tree.set_editable(true);
+        assert!(tree.render().to_string().contains(r#"data-editable="true""#));
+
+        let mut backend = RecordingBackend::new();
+        let node = tree.roots.borrow()[0].id.clone();
+        for name in &["", "b"] {
+            let value = NodeRenamed { node: node.clone(), name: name.to_string() };
+            tree.handle_event(&mut backend, &Event { id: tree.id(), value });
+        }
+        assert_eq!(backend.scripts(), [
+            format!("show_tree_edit_error('{}', '&lt;empty&gt;')", node),
+            format!("update_tree_node('{}', 'b', null, null, null, true)", node),
+        ]);
+        assert_eq!(tree.roots.borrow()[0].caption, "b");
+    }
+
+    struct SlowTree {}
+
+    impl TreeModel<u32> for SlowTree {
+        fn roots(&self) -> Vec<u32> {
+            vec![1, 2, 3]
+        }
+
+        fn children(&self, _parent: &u32) -> Vec<u32> {
+            panic!("children are only loaded by the loader")
+        }
+
+        fn children_loader(&self, parent: &u32) -> Option<ChildrenLoader<u32>> {
+            let parent = *parent;
+            match parent {
+                3 => Some(Box::new(|| Err("No access".into()))),
+                4 => Some(Box::new(|| panic!("Disk failure"))),
+                _ => Some(Box::new(move || Ok(vec![parent * 10]))),
+            }
+        }
+
+        fn caption(&self, node: &u32) -> String {
+            node.to_string()
+        }
+
+        fn has_children(&self, node: &u32) -> bool {
+            *node < 10
+        }
+    }
+
+    #[test]
+    fn test_children_loader() {
+        let mut tree = Tree::new(SlowTree {});
+        tree.render();
+
+        // without background loading the loader runs right away
+        let mut backend = RecordingBackend::new();
+        tree.handle_event(&mut backend, &Event { id: tree.id(), value: NodeExpand(node_id(&tree, 1)) });
+        assert_eq!(tree.visible_nodes(), [node_id(&tree, 1), node_id(&tree, 10), node_id(&tree, 2), node_id(&tree, 3)]);
+
+        // errors are shown, the node can be expanded again
+        backend.take_scripts();
+        tree.handle_event(&mut backend, &Event { id: tree.id(), value: NodeExpand(node_id(&tree, 3)) });
+        assert_eq!(backend.scripts()[1], format!("show_tree_load_error('{}', '{}', 'No access')", tree.id(), node_id(&tree, 3)));
+        assert!(!tree.roots.borrow()[2].children_loaded);
+
+        let loader = tree.model.children_loader(&4).unwrap();
+        assert_eq!(run_loader(loader), Err("Disk failure".to_string()));
+    }
+
+    #[test]
+    fn test_background_loading() {
+        let tree = Tree::new(SlowTree {});
+        let app = App::new("Test");
+        app.set_content(tree.clone());
+        tree.set_background_loading(Some(&app));
+        let mut test_app = TestApp::new(&app).unwrap();
+
+        let parent_id = node_id(&tree, 2);
+        test_app.fire(tree.id(), NodeExpand(parent_id.clone()));
+        assert!(tree.roots.borrow()[1].loading);
+
+        // the children are delivered through the app handle
+        for _ in 0..1000 {
+            test_app.take_scripts();
+            test_app.fire("App", Posted);
+            if !tree.roots.borrow()[1].loading {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(test_app.scripts()[0], format!("remove_tree_node('{}', '{}-loading')", tree.id(), parent_id));
+        assert_eq!(tree.visible_nodes(), [node_id(&tree, 1), node_id(&tree, 2), node_id(&tree, 20), node_id(&tree, 3)]);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::AppError;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Writes the HTML of the page to a file for debugging.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HtmlDump {
+    path: PathBuf,
+    after_events: bool,
+}
+
+impl HtmlDump {
+    /// Dump the initially rendered page to the given file.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        HtmlDump {
+            path: path.into(),
+            after_events: false,
+        }
+    }
+
+    /// Also dump the current state of the DOM after every event handled.
+    pub fn after_events(mut self, after_events: bool) -> Self {
+        self.after_events = after_events;
+        self
+    }
+
+    /// The file the HTML is written to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn is_after_events(&self) -> bool {
+        self.after_events
+    }
+
+    /// Write the HTML to the file, replacing the previous dump.
+    pub(crate) fn write(&self, html: &str) -> Result<(), AppError> {
+        fs::write(&self.path, html).map_err(|io_error| AppError::HtmlDumpError {
+            path: self.path.clone(),
+            reason: io_error.to_string(),
+        })
+    }
+}
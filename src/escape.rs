@@ -0,0 +1,86 @@
+/// Escape a string to be used as text content of an HTML element.
+pub fn escape_html_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape a string to be used as a quoted HTML attribute value.
+pub fn escape_html_attr(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape a string to be used inside a single or double quoted JavaScript string.
+pub fn escape_js_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '<' => escaped.push_str("\\x3C"),
+            '>' => escaped.push_str("\\x3E"),
+            '\u{2028}' => escaped.push_str("\\u2028"),
+            '\u{2029}' => escaped.push_str("\\u2029"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_text() {
+        assert_eq!(escape_html_text("O'Brien \"&\" Co"), "O'Brien \"&amp;\" Co");
+        assert_eq!(
+            escape_html_text("<script>alert(1)</script>"),
+            "&lt;script&gt;alert(1)&lt;/script&gt;"
+        );
+        assert_eq!(escape_html_text("a\\b\nc"), "a\\b\nc");
+    }
+
+    #[test]
+    fn test_html_attr() {
+        assert_eq!(escape_html_attr("O'Brien.txt"), "O&#39;Brien.txt");
+        assert_eq!(escape_html_attr(r#"" onclick="x"#), "&quot; onclick=&quot;x");
+        assert_eq!(escape_html_attr("<b>&amp;</b>"), "&lt;b&gt;&amp;amp;&lt;/b&gt;");
+        assert_eq!(escape_html_attr("a\\b\nc"), "a\\b&#10;c");
+    }
+
+    #[test]
+    fn test_js_string() {
+        assert_eq!(escape_js_string("O'Brien.txt"), "O\\'Brien.txt");
+        assert_eq!(escape_js_string(r#"say "hi""#), r#"say \"hi\""#);
+        assert_eq!(escape_js_string("C:\\temp"), "C:\\\\temp");
+        assert_eq!(escape_js_string("a\nb\r\tc"), "a\\nb\\r\\tc");
+        assert_eq!(escape_js_string("</script>"), "\\x3C/script\\x3E");
+        assert_eq!(escape_js_string("\u{2028}\u{0}"), "\\u2028\\u0000");
+    }
+}
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
-use web_view::WebView;
+use crate::backend::Backend;
+use std::error::Error;
+use std::rc::Rc;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum EventValue {
@@ -11,6 +13,16 @@ pub enum EventValue {
     Created,
     PageLoaded,
     NodeExpand(String),
+    DomDumped(String),
+    CheckedChanged(bool),
+    MenuItemClicked(String),
+    NodeClicked { node: String, ctrl: bool, shift: bool },
+    NodeCollapse(String),
+    NodeDragStart(String),
+    NodeDropped { node: String, target: String },
+    NodeChecked { node: String, checked: bool },
+    NodeRenamed { node: String, name: String },
+    Posted,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -19,8 +31,24 @@ pub struct Event {
     pub value: EventValue,
 }
 
+/// The version of the messages sent from the page.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A message sent from the page, wrapping an event.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Envelope {
+    pub version: u32,
+    pub event: serde_json::Value,
+}
+
+/// Callback for events coming from the page.
+pub type EventHandler = Rc<dyn Fn(&mut dyn Backend, &Event)>;
+
+/// Callback bringing a rendered element up to date after an event has been handled.
+pub type UpdateHook = Rc<dyn Fn(&mut dyn Backend) -> Result<(), Box<dyn Error>>>;
+
 pub struct EventBroker {
-    listeners: HashMap<TypeId, Vec<Box<dyn Fn(&mut WebView<()>, &dyn Any)>>>,
+    listeners: HashMap<TypeId, Vec<Box<dyn Fn(&mut dyn Backend, &dyn Any)>>>,
 }
 
 impl EventBroker {
@@ -30,27 +58,32 @@ impl EventBroker {
         }
     }
 
-    pub fn send<E: Any>(&self, webview: &mut WebView<()>, event: &E) {
-        let type_id = TypeId::of::<E>();
+    pub fn send<E: Any>(&self, backend: &mut dyn Backend, event: &E) {
+        self.send_any(backend, event)
+    }
+
+    /// Send an event whose type is only known at runtime.
+    pub(crate) fn send_any(&self, backend: &mut dyn Backend, event: &dyn Any) {
+        let type_id = event.type_id();
         let listeners = self.listeners.get(&type_id);
         if let Some(l) = listeners {
             for item in l {
-                item(webview, event);
+                item(backend, event);
             }
         }
     }
 
-    fn add_listener<F: Fn(&mut WebView<()>, &dyn Any) + 'static>(&mut self, type_id: TypeId, f: F) {
+    fn add_listener<F: Fn(&mut dyn Backend, &dyn Any) + 'static>(&mut self, type_id: TypeId, f: F) {
         let mut recvs = self.listeners.remove(&type_id).unwrap_or_default();
         recvs.push(Box::new(f));
         self.listeners.insert(type_id, recvs);
     }
 
-    pub fn subscribe<F: Fn(&mut WebView<()>, &E) + 'static, E: Any>(&mut self, f: F) {
+    pub fn subscribe<F: Fn(&mut dyn Backend, &E) + 'static, E: Any>(&mut self, f: F) {
         let type_id = TypeId::of::<E>();
-        self.add_listener(type_id, move |webview, event| {
+        self.add_listener(type_id, move |backend, event| {
             let cast_message: &E = event.downcast_ref().unwrap();
-            f(webview, cast_message);
+            f(backend, cast_message);
         });
     }
 }
@@ -58,6 +91,7 @@ impl EventBroker {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::RecordingBackend;
 
     #[test]
     fn test_clicked() {
@@ -105,11 +139,15 @@ mod tests {
     fn test_event_broker() {
 
         let mut event_broker = EventBroker::new();
-        event_broker.subscribe(|_webview, event: &TestEventEnum| {
+        event_broker.subscribe(|backend, event: &TestEventEnum| {
             dbg!(event);
+            backend.eval(&format!("{:?}", event)).unwrap();
         });
 
-        // event_broker.send(&TestEventEnum::Event1);
-        // event_broker.send(&TestEventEnum::Event2);
+        let mut backend = RecordingBackend::new();
+        event_broker.send(&mut backend, &TestEventEnum::Event1);
+        event_broker.send(&mut backend, &TestEventEnum::Event2);
+
+        assert_eq!(backend.scripts(), ["Event1", "Event2"]);
     }
 }
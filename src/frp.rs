@@ -1,59 +1,314 @@
+use enclose::enclose;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
-use std::cell::RefCell;
 
-#[derive(Clone)]
 pub struct Behavior<T> {
     call: Rc<dyn Fn() -> T>,
+    changes: Option<Event<()>>,
+}
+
+impl<T> Clone for Behavior<T> {
+    fn clone(&self) -> Self {
+        Behavior {
+            call: self.call.clone(),
+            changes: self.changes.clone(),
+        }
+    }
 }
 
 impl<T> Behavior<T> {
+    /// A behavior whose value is computed by `f`, which may change at any time.
     pub fn from_fn(f: impl Fn() -> T + 'static) -> Self {
         Behavior {
             call: Rc::new(f),
+            changes: None,
         }
     }
 
     pub fn value(&self) -> T {
         (self.call)()
     }
+
+    /// An event firing whenever the value changed, `None` if the behavior
+    /// cannot tell, like the ones created with `from_fn`.
+    pub fn changes(&self) -> Option<Event<()>> {
+        self.changes.clone()
+    }
+}
+
+impl<T: 'static> Behavior<T> {
+    /// A behavior always having the same value.
+    pub fn constant(value: T) -> Self
+    where
+        T: Clone,
+    {
+        Behavior {
+            call: Rc::new(move || value.clone()),
+            changes: Some(Event::new()),
+        }
+    }
+
+    /// A behavior whose value is derived from this behavior's value.
+    pub fn map<U>(&self, f: impl Fn(T) -> U + 'static) -> Behavior<U> {
+        let call = self.call.clone();
+        Behavior {
+            call: Rc::new(move || f(call())),
+            changes: self.changes.clone(),
+        }
+    }
+
+    /// A behavior combining the values of this and another behavior.
+    pub fn zip<U: 'static>(&self, other: &Behavior<U>) -> Behavior<(T, U)> {
+        lift2(self, other, |a, b| (a, b))
+    }
+
+    /// An event firing the current value of this behavior whenever `event` fires.
+    pub fn sample<E: 'static>(&self, event: &Event<E>) -> Event<T> {
+        self.snapshot(event, |_, value| value)
+    }
+
+    /// An event firing a combination of `event`'s values and the current value
+    /// of this behavior whenever `event` fires.
+    pub fn snapshot<E: 'static, R: 'static>(
+        &self,
+        event: &Event<E>,
+        f: impl Fn(&E, T) -> R + 'static,
+    ) -> Event<R> {
+        let snapshots = Event::new();
+        let call = self.call.clone();
+        event.listen(enclose!((snapshots) move |e| snapshots.send(&f(e, call()))));
+        snapshots
+    }
+}
+
+impl From<String> for Behavior<String> {
+    fn from(value: String) -> Self {
+        Behavior::constant(value)
+    }
+}
+
+impl From<&str> for Behavior<String> {
+    fn from(value: &str) -> Self {
+        Behavior::constant(value.to_string())
+    }
+}
+
+impl From<bool> for Behavior<bool> {
+    fn from(value: bool) -> Self {
+        Behavior::constant(value)
+    }
+}
+
+/// A behavior whose value is derived from the values of two behaviors.
+pub fn lift2<A: 'static, B: 'static, R>(
+    a: &Behavior<A>,
+    b: &Behavior<B>,
+    f: impl Fn(A, B) -> R + 'static,
+) -> Behavior<R> {
+    let changes = match (&a.changes, &b.changes) {
+        (Some(a), Some(b)) => Some(a.merge(b)),
+        _ => None,
+    };
+    let (a, b) = (a.call.clone(), b.call.clone());
+    Behavior {
+        call: Rc::new(move || f(a(), b())),
+        changes,
+    }
+}
+
+type Callback<T> = Rc<dyn Fn(&T)>;
+
+struct Listeners<T> {
+    next_id: usize,
+    callbacks: Vec<(usize, Callback<T>)>,
+}
+
+impl<T> Default for Listeners<T> {
+    fn default() -> Self {
+        Listeners {
+            next_id: 0,
+            callbacks: Vec::new(),
+        }
+    }
 }
 
-#[derive(Clone)]
 pub struct Event<T> {
-    cbs: Rc<RefCell<Vec<Box< dyn Fn(&T)>>>>,
+    listeners: Rc<RefCell<Listeners<T>>>,
+}
+
+impl<T> Clone for Event<T> {
+    fn clone(&self) -> Self {
+        Event {
+            listeners: self.listeners.clone(),
+        }
+    }
+}
+
+impl<E: 'static> Default for Event<E> {
+    fn default() -> Self {
+        Event::new()
+    }
 }
 
 impl<E: 'static> Event<E> {
     pub fn new() -> Self {
         Event {
-            cbs: Default::default(),
+            listeners: Default::default(),
         }
     }
 
+    /// An event together with the sink firing it.
     pub fn new_with_sink() -> (Self, Sink<E>) {
         let event = Event::new();
-        let sink = Sink::from_fn(|e| {
-            event.send(e)
-        });
+        let sink = Sink::from_fn(enclose!((event) move |e| event.send(e)));
         (event, sink)
     }
 
-    fn observe(&self, f: impl Fn(&E) + 'static) {
-        self.cbs.borrow_mut().push(Box::new(f))
+    /// Call `f` whenever the event fires until the returned listener is unlistened.
+    pub fn listen(&self, f: impl Fn(&E) + 'static) -> Listener {
+        let id = {
+            let mut listeners = self.listeners.borrow_mut();
+            let id = listeners.next_id;
+            listeners.next_id += 1;
+            listeners.callbacks.push((id, Rc::new(f)));
+            id
+        };
+        let listeners = Rc::downgrade(&self.listeners);
+        Listener {
+            unlisten: Box::new(move || {
+                if let Some(listeners) = listeners.upgrade() {
+                    listeners.borrow_mut().callbacks.retain(|(other, _)| *other != id);
+                }
+            }),
+        }
+    }
+
+    /// Whether anybody listens to this event.
+    pub fn has_listeners(&self) -> bool {
+        !self.listeners.borrow().callbacks.is_empty()
     }
 
     fn send(&self, event: &E) {
-        for cb in &*self.cbs.borrow() {
-            cb(event)
+        // Listeners may (un)listen while being called, so call a copy.
+        let callbacks: Vec<_> = self
+            .listeners
+            .borrow()
+            .callbacks
+            .iter()
+            .map(|(_, callback)| callback.clone())
+            .collect();
+        for callback in callbacks {
+            callback(event)
+        }
+    }
+
+    /// An event firing for every occurrence of this event with a derived value.
+    pub fn map<U: 'static>(&self, f: impl Fn(&E) -> U + 'static) -> Event<U> {
+        self.filter_map(move |e| Some(f(e)))
+    }
+
+    /// An event firing only for the occurrences of this event matching `predicate`.
+    pub fn filter(&self, predicate: impl Fn(&E) -> bool + 'static) -> Event<E> {
+        let filtered = Event::new();
+        self.listen(enclose!((filtered) move |e| {
+            if predicate(e) {
+                filtered.send(e)
+            }
+        }));
+        filtered
+    }
+
+    /// An event firing a derived value for the occurrences of this event `f`
+    /// returns a value for.
+    pub fn filter_map<U: 'static>(&self, f: impl Fn(&E) -> Option<U> + 'static) -> Event<U> {
+        let mapped = Event::new();
+        self.listen(enclose!((mapped) move |e| {
+            if let Some(value) = f(e) {
+                mapped.send(&value)
+            }
+        }));
+        mapped
+    }
+
+    /// An event firing for the occurrences of both this and another event.
+    pub fn merge(&self, other: &Event<E>) -> Event<E> {
+        let merged = Event::new();
+        self.listen(enclose!((merged) move |e| merged.send(e)));
+        other.listen(enclose!((merged) move |e| merged.send(e)));
+        merged
+    }
+
+    /// An event firing only for the first occurrence of this event.
+    pub fn once(&self) -> Event<E> {
+        let first = Event::new();
+        let listener: Rc<RefCell<Option<Listener>>> = Default::default();
+        let fired = Rc::new(Cell::new(false));
+        let new_listener = self.listen(enclose!((first, listener) move |e| {
+            if !fired.replace(true) {
+                if let Some(listener) = listener.borrow_mut().take() {
+                    listener.unlisten();
+                }
+                first.send(e)
+            }
+        }));
+        listener.borrow_mut().replace(new_listener);
+        first
+    }
+
+    /// A behavior starting with `initial` and updated with `f` whenever this
+    /// event fires.
+    pub fn accumulate<S: Clone + 'static>(
+        &self,
+        initial: S,
+        f: impl Fn(S, &E) -> S + 'static,
+    ) -> Behavior<S> {
+        let state = Rc::new(RefCell::new(initial));
+        let changes = Event::new();
+        self.listen(enclose!((state, changes) move |e| {
+            let current = state.borrow().clone();
+            *state.borrow_mut() = f(current, e);
+            changes.send(&());
+        }));
+        Behavior {
+            call: Rc::new(move || state.borrow().clone()),
+            changes: Some(changes),
         }
     }
+
+    /// A behavior holding the value of the latest occurrence of this event,
+    /// starting with `initial`.
+    pub fn hold(&self, initial: E) -> Behavior<E>
+    where
+        E: Clone,
+    {
+        self.accumulate(initial, |_, e| e.clone())
+    }
+}
+
+/// Handle to stop listening to an event.
+pub struct Listener {
+    unlisten: Box<dyn Fn()>,
+}
+
+impl Listener {
+    /// Stop calling the listener's function.
+    pub fn unlisten(self) {
+        (self.unlisten)()
+    }
 }
 
-#[derive(Clone)]
 pub struct Sink<T> {
     call: Rc<dyn Fn(&T)>,
 }
 
+impl<T> Clone for Sink<T> {
+    fn clone(&self) -> Self {
+        Sink {
+            call: self.call.clone(),
+        }
+    }
+}
+
 impl<T> Sink<T> {
     pub fn from_fn(f: impl Fn(&T) + 'static) -> Self {
         Sink {
@@ -64,4 +319,143 @@ impl<T> Sink<T> {
     pub fn send(&self, value: &T) {
         (self.call)(value)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> (Behavior<String>, Rc<RefCell<String>>) {
+        let cell = Rc::new(RefCell::new(value.to_string()));
+        let source = cell.clone();
+        (Behavior::from_fn(move || source.borrow().clone()), cell)
+    }
+
+    #[test]
+    fn test_constant() {
+        assert_eq!(Behavior::constant(42).value(), 42);
+    }
+
+    #[test]
+    fn test_map() {
+        let (name, cell) = text("");
+        let empty = name.map(|name| name.is_empty());
+        assert!(empty.value());
+        *cell.borrow_mut() = "Joe".into();
+        assert!(!empty.value());
+    }
+
+    #[test]
+    fn test_zip_and_lift2() {
+        let (name, name_cell) = text("Joe");
+        let (password, password_cell) = text("");
+        let both = name.zip(&password);
+        let enabled = lift2(&name, &password, |name, password| !name.is_empty() && !password.is_empty());
+        assert_eq!(both.value(), ("Joe".to_string(), "".to_string()));
+        assert!(!enabled.value());
+
+        *password_cell.borrow_mut() = "secret".into();
+        assert!(enabled.value());
+        name_cell.borrow_mut().clear();
+        assert!(!enabled.value());
+        assert_eq!(both.value(), ("".to_string(), "secret".to_string()));
+    }
+
+    #[test]
+    fn test_sample() {
+        let (name, cell) = text("Joe");
+        let clicks = Event::<()>::new();
+        let sampled = name.sample(&clicks);
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let sink = received.clone();
+        sampled.listen(move |name: &String| sink.borrow_mut().push(name.clone()));
+
+        clicks.send(&());
+        *cell.borrow_mut() = "Ann".into();
+        clicks.send(&());
+        assert_eq!(*received.borrow(), vec!["Joe".to_string(), "Ann".to_string()]);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let (name, _cell) = text("Joe");
+        let keys = Event::<char>::new();
+        let snapshots = name.snapshot(&keys, |key, name| format!("{}{}", name, key));
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let sink = received.clone();
+        snapshots.listen(move |value: &String| sink.borrow_mut().push(value.clone()));
+
+        keys.send(&'!');
+        assert_eq!(*received.borrow(), vec!["Joe!".to_string()]);
+    }
+
+    fn record<E: Clone + 'static>(event: &Event<E>) -> (Rc<RefCell<Vec<E>>>, Listener) {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let listener = event.listen(enclose!((received) move |e: &E| received.borrow_mut().push(e.clone())));
+        (received, listener)
+    }
+
+    #[test]
+    fn test_listen() {
+        let (event, sink) = Event::new_with_sink();
+        let (received, listener) = record(&event);
+        sink.send(&1);
+        sink.clone().send(&2);
+        listener.unlisten();
+        sink.send(&3);
+        assert_eq!(*received.borrow(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_map_filter() {
+        let (event, sink) = Event::new_with_sink();
+        let (doubled, _) = record(&event.map(|n| n * 2));
+        let (even, _) = record(&event.filter(|n| n % 2 == 0));
+        let (parsed, _) = record(&event.filter_map(|n: &i32| if *n > 1 { Some(n.to_string()) } else { None }));
+        for n in 1..=3 {
+            sink.send(&n);
+        }
+        assert_eq!(*doubled.borrow(), vec![2, 4, 6]);
+        assert_eq!(*even.borrow(), vec![2]);
+        assert_eq!(*parsed.borrow(), vec!["2".to_string(), "3".to_string()]);
+    }
+
+    #[test]
+    fn test_merge_once() {
+        let (left, left_sink) = Event::new_with_sink();
+        let (right, right_sink) = Event::new_with_sink();
+        let (merged, _) = record(&left.merge(&right));
+        let (first, _) = record(&left.once());
+        left_sink.send(&'a');
+        right_sink.send(&'b');
+        left_sink.send(&'c');
+        assert_eq!(*merged.borrow(), vec!['a', 'b', 'c']);
+        assert_eq!(*first.borrow(), vec!['a']);
+    }
+
+    #[test]
+    fn test_accumulate_hold() {
+        let (event, sink) = Event::new_with_sink();
+        let sum = event.accumulate(0, |sum, n| sum + n);
+        let latest = event.hold(0);
+        assert_eq!((sum.value(), latest.value()), (0, 0));
+        sink.send(&2);
+        sink.send(&3);
+        assert_eq!((sum.value(), latest.value()), (5, 3));
+    }
+
+    #[test]
+    fn test_changes() {
+        let (event, sink) = Event::new_with_sink();
+        let sum = event.accumulate(0, |sum, n| sum + n);
+        let label = lift2(&sum.map(|sum| sum.to_string()), &Behavior::constant("items"), |n, unit| format!("{} {}", n, unit));
+        let (changes, _) = record(&label.changes().unwrap());
+        sink.send(&2);
+        assert_eq!(changes.borrow().len(), 1);
+        assert_eq!(label.value(), "2 items");
+
+        let (name, _cell) = text("Joe");
+        assert!(name.changes().is_none());
+        assert!(lift2(&name, &sum, |name, _| name).changes().is_none());
+    }
+}
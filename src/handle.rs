@@ -0,0 +1,151 @@
+use crate::backend::Backend;
+use crate::AppError;
+use log::error;
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+use web_view::Handle;
+
+/// A closure posted to the UI thread.
+type PostedClosure = Box<dyn FnOnce(&mut dyn Backend) + Send>;
+
+/// Work posted to the UI thread.
+pub(crate) enum Posted {
+    Closure(PostedClosure),
+    Message(Box<dyn Any + Send>),
+}
+
+/// A handle to the UI thread of an app, to be used from other threads.
+///
+/// Worker threads post closures or typed messages with it. They are run on the
+/// UI thread resp. sent to the subscribers of the app, the same way as events
+/// coming from the page. Work posted before the app runs is delivered once its
+/// window has been created.
+#[derive(Clone)]
+pub struct AppHandle {
+    state: Arc<Mutex<HandleState>>,
+}
+
+/// Lets the UI thread take the posted work.
+type Waker = Arc<dyn Fn() -> Result<(), AppError> + Send + Sync>;
+
+/// The posted work together with the way to wake the UI thread, guarded by
+/// one lock so no work is posted unnoticed while the window is attached.
+struct HandleState {
+    queue: Vec<Posted>,
+    waker: Option<Waker>,
+}
+
+impl AppHandle {
+    pub(crate) fn new() -> Self {
+        AppHandle {
+            state: Arc::new(Mutex::new(HandleState { queue: Vec::new(), waker: None })),
+        }
+    }
+
+    /// Run `f` on the UI thread.
+    ///
+    /// Fails with `AppClosedError` after the window has been closed, the closure is dropped then.
+    pub fn post(&self, f: impl FnOnce(&mut dyn Backend) + Send + 'static) -> Result<(), AppError> {
+        self.push(Posted::Closure(Box::new(f)))
+    }
+
+    /// Send `message` to the subscribers of its type on the UI thread.
+    ///
+    /// Fails with `AppClosedError` after the window has been closed, the message is dropped then.
+    pub fn send<E: Any + Send>(&self, message: E) -> Result<(), AppError> {
+        self.push(Posted::Message(Box::new(message)))
+    }
+
+    fn push(&self, posted: Posted) -> Result<(), AppError> {
+        let waker = {
+            let mut state = self.state.lock().unwrap();
+            state.queue.push(posted);
+            state.waker.clone()
+        };
+        match waker {
+            Some(waker) => self.wake(&waker),
+            None => Ok(()),
+        }
+    }
+
+    /// Let the UI thread take the posted work, dropping it if the window is gone.
+    fn wake(&self, waker: &Waker) -> Result<(), AppError> {
+        let result = waker();
+        if result.is_err() {
+            self.state.lock().unwrap().queue.clear();
+        }
+        result
+    }
+
+    /// Connect the handle to the window of the running app.
+    pub(crate) fn attach(&self, window: Handle<()>) {
+        self.attach_waker(Arc::new(move || {
+            window
+                .dispatch(|webview| webview.eval(r#"fire('App', "Posted")"#))
+                .map_err(|_| AppError::AppClosedError)
+        }));
+    }
+
+    fn attach_waker(&self, waker: Waker) {
+        let pending = {
+            let mut state = self.state.lock().unwrap();
+            state.waker = Some(waker.clone());
+            !state.queue.is_empty()
+        };
+        if pending {
+            if let Err(app_error) = self.wake(&waker) {
+                error!("{}", app_error);
+            }
+        }
+    }
+
+    /// Take all work posted so far.
+    pub(crate) fn take_posted(&self) -> Vec<Posted> {
+        std::mem::take(&mut self.state.lock().unwrap().queue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn test_post_while_attaching() {
+        for _ in 0..100 {
+            let handle = AppHandle::new();
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    let handle = handle.clone();
+                    thread::spawn(move || {
+                        for value in 0..10 {
+                            handle.send(value).unwrap();
+                        }
+                    })
+                })
+                .collect();
+
+            // the waker delivers right away, like the UI thread handling the event
+            let delivered = Arc::new(AtomicUsize::new(0));
+            let (waker_handle, waker_delivered) = (handle.clone(), delivered.clone());
+            handle.attach_waker(Arc::new(move || {
+                waker_delivered.fetch_add(waker_handle.take_posted().len(), Ordering::SeqCst);
+                Ok(())
+            }));
+            for worker in workers {
+                worker.join().unwrap();
+            }
+            assert_eq!(delivered.load(Ordering::SeqCst), 40);
+        }
+    }
+
+    #[test]
+    fn test_closed_window() {
+        let handle = AppHandle::new();
+        handle.send(1).unwrap();
+        handle.attach_waker(Arc::new(|| Err(AppError::AppClosedError)));
+        assert_eq!(handle.send(2), Err(AppError::AppClosedError));
+        assert!(handle.take_posted().is_empty());
+    }
+}
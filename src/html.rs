@@ -0,0 +1,350 @@
+use crate::escape::{escape_html_attr, escape_html_text};
+use crate::event::{EventHandler, UpdateHook};
+use std::fmt;
+
+/// Elements without content and closing tag.
+const VOID_ELEMENTS: [&str; 6] = ["br", "hr", "img", "input", "link", "meta"];
+
+/// A node of the rendered page.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    /// An HTML element with attributes and children.
+    Element(Element),
+    /// Text which gets escaped when rendered.
+    Text(String),
+    /// Markup which is rendered as is.
+    Raw(String),
+}
+
+impl Node {
+    /// Create a text node.
+    pub fn text(text: impl Into<String>) -> Self {
+        Node::Text(text.into())
+    }
+
+    /// Create a node with markup which will not be escaped.
+    pub fn raw(markup: impl Into<String>) -> Self {
+        Node::Raw(markup.into())
+    }
+
+    /// The element of this node if it is one.
+    pub fn as_element(&self) -> Option<&Element> {
+        match self {
+            Node::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    /// Find the first element in this subtree (including itself) matching the predicate.
+    pub fn find(&self, predicate: impl Fn(&Element) -> bool + Copy) -> Option<&Element> {
+        match self {
+            Node::Element(element) => element.find(predicate),
+            _ => None,
+        }
+    }
+
+    /// Find an element in this subtree by its id.
+    pub fn find_by_id(&self, id: &str) -> Option<&Element> {
+        self.find(|element| element.attribute("id") == Some(id))
+    }
+
+    /// Replace the element with the given id in this subtree.
+    /// The new node is given back if there is no such element.
+    pub(crate) fn replace_by_id(&mut self, id: &str, node: Node) -> Result<(), Node> {
+        let element = match self {
+            Node::Element(element) => element,
+            _ => return Err(node),
+        };
+        if element.attribute("id") == Some(id) {
+            *self = node;
+            return Ok(());
+        }
+
+        let mut node = node;
+        for child in &mut element.children {
+            match child.replace_by_id(id, node) {
+                Ok(()) => return Ok(()),
+                Err(not_replaced) => node = not_replaced,
+            }
+        }
+        Err(node)
+    }
+}
+
+impl From<Element> for Node {
+    fn from(element: Element) -> Self {
+        Node::Element(element)
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Element(element) => element.fmt(f),
+            Node::Text(text) => f.write_str(&escape_html_text(text)),
+            Node::Raw(markup) => f.write_str(markup),
+        }
+    }
+}
+
+/// An HTML element built from typed attributes and children.
+#[derive(Clone)]
+pub struct Element {
+    tag: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Node>,
+    handler: Option<EventHandler>,
+    update: Option<UpdateHook>,
+}
+
+impl Element {
+    /// Create a new element without attributes and children.
+    pub fn new(tag: impl Into<String>) -> Self {
+        Element {
+            tag: tag.into(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+            handler: None,
+            update: None,
+        }
+    }
+
+    /// Set an attribute. The value will be escaped when rendered.
+    pub fn attr(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        let name = name.into();
+        let value = value.to_string();
+        match self.attributes.iter_mut().find(|(n, _)| *n == name) {
+            Some(attribute) => attribute.1 = value,
+            None => self.attributes.push((name, value)),
+        }
+        self
+    }
+
+    /// Set an attribute whose value Metro inserts as HTML into the page (e.g. `data-caption`).
+    pub fn caption(self, name: impl Into<String>, caption: &str) -> Self {
+        self.attr(name, escape_html_text(caption))
+    }
+
+    /// Set the id of the element.
+    pub fn id(self, id: impl ToString) -> Self {
+        self.attr("id", id)
+    }
+
+    /// Add one or more CSS classes separated by spaces.
+    pub fn class(self, class: &str) -> Self {
+        match self.attribute("class") {
+            Some(existing) => {
+                let classes = format!("{} {}", existing, class);
+                self.attr("class", classes)
+            }
+            None => self.attr("class", class),
+        }
+    }
+
+    /// Set the inline style of the element.
+    pub fn style(self, style: impl ToString) -> Self {
+        self.attr("style", style)
+    }
+
+    /// Set a `data-*` attribute, e.g. `data("role", "panel")`.
+    pub fn data(self, name: &str, value: impl ToString) -> Self {
+        self.attr(format!("data-{}", name), value)
+    }
+
+    /// Set an inline event handler, e.g. `on("click", "fire_clicked('id')")`.
+    pub fn on(self, event: &str, script: impl ToString) -> Self {
+        self.attr(format!("on{}", event), script)
+    }
+
+    /// Set the handler for all events fired with the id of this element.
+    pub fn on_event(mut self, handler: EventHandler) -> Self {
+        self.handler = Some(handler);
+        self
+    }
+
+    /// Set a hook called after every event handled while this element is part of the page.
+    pub fn on_update(mut self, hook: UpdateHook) -> Self {
+        self.update = Some(hook);
+        self
+    }
+
+    /// Add a child node.
+    pub fn child(mut self, child: impl Into<Node>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    /// Add several child nodes.
+    pub fn children(mut self, children: impl IntoIterator<Item = Node>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    /// Add a text child.
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.child(Node::text(text))
+    }
+
+    /// The tag name of the element.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// The value of an attribute.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// All attributes in the order they were set.
+    pub fn attributes(&self) -> &[(String, String)] {
+        &self.attributes
+    }
+
+    /// The child nodes of the element.
+    pub fn child_nodes(&self) -> &[Node] {
+        &self.children
+    }
+
+    /// The handler for events fired with the id of this element.
+    pub fn event_handler(&self) -> Option<&EventHandler> {
+        self.handler.as_ref()
+    }
+
+    /// The hook updating this element after events.
+    pub fn update_hook(&self) -> Option<&UpdateHook> {
+        self.update.as_ref()
+    }
+
+    /// The concatenated text of all text nodes below this element.
+    pub fn text_content(&self) -> String {
+        let mut text = String::new();
+        for child in &self.children {
+            match child {
+                Node::Element(element) => text.push_str(&element.text_content()),
+                Node::Text(t) => text.push_str(t),
+                Node::Raw(_) => {}
+            }
+        }
+        text
+    }
+
+    /// Find the first element in this subtree (including itself) matching the predicate.
+    pub fn find(&self, predicate: impl Fn(&Element) -> bool + Copy) -> Option<&Element> {
+        if predicate(self) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(predicate))
+    }
+
+    fn is_void(&self) -> bool {
+        VOID_ELEMENTS.contains(&self.tag.as_str())
+    }
+}
+
+impl PartialEq for Element {
+    /// Elements are equal if they render the same markup, handlers and hooks are not compared.
+    fn eq(&self, other: &Self) -> bool {
+        self.tag == other.tag && self.attributes == other.attributes && self.children == other.children
+    }
+}
+
+impl fmt::Debug for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Element")
+            .field("tag", &self.tag)
+            .field("attributes", &self.attributes)
+            .field("children", &self.children)
+            .field("handler", &self.handler.is_some())
+            .field("update", &self.update.is_some())
+            .finish()
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}", self.tag)?;
+        for (name, value) in &self.attributes {
+            write!(f, " {}=\"{}\"", name, escape_html_attr(value))?;
+        }
+        write!(f, ">")?;
+        if self.is_void() {
+            return Ok(());
+        }
+        for child in &self.children {
+            child.fmt(f)?;
+        }
+        write!(f, "</{}>", self.tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let node: Node = Element::new("div")
+            .id("id-1")
+            .class("w-100")
+            .class("h-100")
+            .data("role", "panel")
+            .child(Element::new("a").on("click", "fire_clicked('id-1')").text("Click"))
+            .into();
+
+        assert_eq!(
+            node.to_string(),
+            r#"<div id="id-1" class="w-100 h-100" data-role="panel"><a onclick="fire_clicked(&#39;id-1&#39;)">Click</a></div>"#
+        );
+    }
+
+    #[test]
+    fn test_escape_by_default() {
+        let node: Node = Element::new("span")
+            .attr("title", r#""><script>"#)
+            .text("<script>alert('x')</script>")
+            .into();
+
+        assert_eq!(
+            node.to_string(),
+            r#"<span title="&quot;&gt;&lt;script&gt;">&lt;script&gt;alert('x')&lt;/script&gt;</span>"#
+        );
+    }
+
+    #[test]
+    fn test_caption() {
+        let element = Element::new("li").caption("data-caption", "<b>O'Brien</b>");
+        assert_eq!(element.attribute("data-caption"), Some("&lt;b&gt;O'Brien&lt;/b&gt;"));
+        assert_eq!(
+            element.to_string(),
+            r#"<li data-caption="&amp;lt;b&amp;gt;O&#39;Brien&amp;lt;/b&amp;gt;"></li>"#
+        );
+    }
+
+    #[test]
+    fn test_void_and_raw() {
+        let node: Node = Element::new("div")
+            .child(Element::new("input").attr("value", "a"))
+            .child(Node::raw("<br>"))
+            .into();
+        assert_eq!(node.to_string(), r#"<div><input value="a"><br></div>"#);
+    }
+
+    #[test]
+    fn test_inspect() {
+        let node: Node = Element::new("ul")
+            .id("list")
+            .child(Element::new("li").id("first").text("One"))
+            .child(Element::new("li").id("second").text("Two"))
+            .into();
+
+        let second = node.find_by_id("second").unwrap();
+        assert_eq!(second.tag(), "li");
+        assert_eq!(second.text_content(), "Two");
+        assert_eq!(node.as_element().unwrap().child_nodes().len(), 2);
+        assert_eq!(node.find(|e| e.tag() == "li").unwrap().attribute("id"), Some("first"));
+        assert!(node.find_by_id("third").is_none());
+    }
+}
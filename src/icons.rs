@@ -1,11 +1,788 @@
+use crate::html::{Element, Node};
+use std::path::Path;
+
+/// An icon to be shown in components.
+#[derive(Clone, Debug, PartialEq)]
 pub enum Icon {
     MifIcon(MifIcon),
-    FileIcon(),
+    /// The icon for a type of file, keyed by its extension without dot, e.g. `"pdf"`.
+    FileIcon(String),
 }
 
-pub enum MifIcon {
-    Home,
-    Copy,
-    Exit,
-    FloppyDisk,
+impl Icon {
+    /// The icon for the type of the given file.
+    pub fn for_file(path: impl AsRef<Path>) -> Self {
+        let extension = path.as_ref().extension().and_then(|e| e.to_str()).unwrap_or("");
+        Icon::FileIcon(extension.into())
+    }
+
+    /// The icon of the Metro icon font showing this icon.
+    pub fn mif_icon(&self) -> MifIcon {
+        match self {
+            Icon::MifIcon(icon) => *icon,
+            Icon::FileIcon(extension) => file_icon(extension),
+        }
+    }
+
+    /// Render the icon as an element of the Metro icon font.
+    pub fn render(&self) -> Node {
+        Element::new("span")
+            .class(&format!("mif-{} icon", self.mif_icon().name()))
+            .into()
+    }
+
+    /// Render only the glyph, for widgets wrapping it into an icon container themselves.
+    pub(crate) fn glyph(&self) -> Node {
+        Element::new("span")
+            .class(&format!("mif-{}", self.mif_icon().name()))
+            .into()
+    }
+}
+
+impl From<MifIcon> for Icon {
+    fn from(icon: MifIcon) -> Self {
+        Icon::MifIcon(icon)
+    }
+}
+
+/// The icon for files with the given extension.
+fn file_icon(extension: &str) -> MifIcon {
+    match extension.to_lowercase().as_str() {
+        "zip" | "7z" | "rar" => MifIcon::FileZip,
+        "tar" | "gz" | "tgz" | "bz2" | "xz" => MifIcon::FileArchive,
+        "mp3" | "wav" | "ogg" | "flac" | "aac" | "m4a" => MifIcon::FileAudio,
+        "mp4" | "avi" | "mkv" | "mov" | "webm" | "wmv" => MifIcon::FileVideo,
+        "png" | "jpg" | "jpeg" | "gif" | "bmp" | "svg" | "webp" | "ico" | "tif" | "tiff" => MifIcon::FileImage,
+        "pdf" => MifIcon::FilePdf,
+        "doc" | "docx" | "rtf" => MifIcon::FileWord,
+        "xls" | "xlsx" | "csv" => MifIcon::FileExcel,
+        "ppt" | "pptx" => MifIcon::FilePowerpoint,
+        "odt" | "ods" | "odp" | "odg" => MifIcon::FileOpenoffice,
+        "txt" | "md" | "log" => MifIcon::FileText,
+        "rs" | "c" | "h" | "cpp" | "hpp" | "java" | "js" | "ts" | "py" | "go" | "html" | "css" | "json"
+        | "toml" | "xml" | "yml" | "yaml" | "sh" => MifIcon::FileCode,
+        "exe" | "bin" | "dll" | "so" | "o" => MifIcon::FileBinary,
+        _ => MifIcon::FileEmpty,
+    }
+}
+
+macro_rules! mif_icons {
+    ($($variant:ident => $name:expr,)*) => {
+        /// The icons of the Metro icon font.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum MifIcon {
+            $($variant,)*
+        }
+
+        impl MifIcon {
+            /// The name of the icon in the Metro icon font, e.g. `floppy-disk` for `mif-floppy-disk`.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(MifIcon::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+mif_icons! {
+    Rotation3d => "3d-rotation",
+    Accessible => "accessible",
+    Adb => "adb",
+    AddPhoto => "add-photo",
+    AddShoppingCart => "add-shopping-cart",
+    Add => "add",
+    Air => "air",
+    Airplane => "airplane",
+    AlarmOn => "alarm-on",
+    Alarm => "alarm",
+    Amazon => "amazon",
+    Ambulance => "ambulance",
+    Amex => "amex",
+    Anchor => "anchor",
+    Android => "android",
+    Apple => "apple",
+    Apps => "apps",
+    ArrowDownLeft => "arrow-down-left",
+    ArrowDownRight => "arrow-down-right",
+    ArrowDown => "arrow-down",
+    ArrowDropDown => "arrow-drop-down",
+    ArrowDropUp => "arrow-drop-up",
+    ArrowLeft => "arrow-left",
+    ArrowRight => "arrow-right",
+    ArrowUpLeft => "arrow-up-left",
+    ArrowUpRight => "arrow-up-right",
+    ArrowUp => "arrow-up",
+    Assignment => "assignment",
+    Attachment => "attachment",
+    Automobile => "automobile",
+    Backspace1 => "backspace1",
+    Backspace => "backspace",
+    Backward => "backward",
+    BalanceScale => "balance-scale",
+    Barbell => "barbell",
+    Barcode => "barcode",
+    BatteryCharge => "battery-charge",
+    BatteryEmpty => "battery-empty",
+    BatteryFull => "battery-full",
+    BatteryOne => "battery-one",
+    BatteryTwo => "battery-two",
+    Beenhere => "beenhere",
+    Bell => "bell",
+    Bicycle => "bicycle",
+    Bin => "bin",
+    Bitcoin => "bitcoin",
+    Blocked => "blocked",
+    Blogger => "blogger",
+    Bluetooth => "bluetooth",
+    Bold => "bold",
+    BookReference => "book-reference",
+    Bookmark => "bookmark",
+    Bookmarks => "bookmarks",
+    Books => "books",
+    BrightnessAuto => "brightness-auto",
+    Brightness => "brightness",
+    Broadcast => "broadcast",
+    BtAudio => "bt-audio",
+    BtConnected => "bt-connected",
+    BtDisabled => "bt-disabled",
+    BtSearching => "bt-searching",
+    BtSettings => "bt-settings",
+    Bubble => "bubble",
+    Bubbles => "bubbles",
+    Bug => "bug",
+    Bus => "bus",
+    Cab => "cab",
+    Cabinet => "cabinet",
+    Cake => "cake",
+    Calculator2 => "calculator2",
+    Calculator => "calculator",
+    Calendar => "calendar",
+    CameraFront => "camera-front",
+    CameraRear => "camera-rear",
+    Camera => "camera",
+    Cancel => "cancel",
+    Cart => "cart",
+    CastConnected => "cast-connected",
+    Cast => "cast",
+    CcPaypal => "cc-paypal",
+    CellNoInternet => "cell-no-internet",
+    CellNull => "cell-null",
+    CellOff => "cell-off",
+    CellOn => "cell-on",
+    CellSetting => "cell-setting",
+    Celsius => "celsius",
+    ChartBars2 => "chart-bars2",
+    ChartBars => "chart-bars",
+    ChartDots => "chart-dots",
+    ChartLine => "chart-line",
+    ChartPie => "chart-pie",
+    ChatBubbleOutline => "chat-bubble-outline",
+    ChatBubble => "chat-bubble",
+    Chat => "chat",
+    Checkmark => "checkmark",
+    ChevronLeft => "chevron-left",
+    ChevronRight => "chevron-right",
+    ChevronThinDown => "chevron-thin-down",
+    ChevronThinLeft => "chevron-thin-left",
+    ChevronThinRight => "chevron-thin-right",
+    ChevronThinUp => "chevron-thin-up",
+    Chrome => "chrome",
+    Clipboard => "clipboard",
+    CloudDownload => "cloud-download",
+    CloudUpload => "cloud-upload",
+    Cloud2 => "cloud2",
+    Cloud3 => "cloud3",
+    Cloud4 => "cloud4",
+    Cloud5 => "cloud5",
+    Cloud6 => "cloud6",
+    Cloud => "cloud",
+    Cloudy2 => "cloudy2",
+    Cloudy3 => "cloudy3",
+    Cloudy4 => "cloudy4",
+    Cloudy => "cloudy",
+    Cny => "cny",
+    Codepen => "codepen",
+    Cog => "cog",
+    Cogs => "cogs",
+    Coins => "coins",
+    Command => "command",
+    Comment => "comment",
+    Compass2 => "compass2",
+    Compass => "compass",
+    ContactsDialer => "contacts-dialer",
+    ContactsMail => "contacts-mail",
+    Contrast => "contrast",
+    Copy => "copy",
+    Copyright => "copyright",
+    CreateNewFolder => "create-new-folder",
+    CreativeCloud => "creative-cloud",
+    CreativeCommons => "creative-commons",
+    CreditCard => "credit-card",
+    Crop => "crop",
+    CrossLight => "cross-light",
+    Cross => "cross",
+    Css3 => "css3",
+    Cut => "cut",
+    Dashboard => "dashboard",
+    Database => "database",
+    Delicious => "delicious",
+    Description => "description",
+    Deviantart => "deviantart",
+    Devices => "devices",
+    DialerSip => "dialer-sip",
+    Dialpad => "dialpad",
+    Dice => "dice",
+    Digg => "digg",
+    DirectionsBike => "directions-bike",
+    Discout => "discout",
+    Discover => "discover",
+    Display => "display",
+    DocumentFile3gp => "document-file-3gp",
+    DocumentFileAac => "document-file-aac",
+    DocumentFileAi => "document-file-ai",
+    DocumentFileAiff => "document-file-aiff",
+    DocumentFileApp => "document-file-app",
+    DocumentFileAsp => "document-file-asp",
+    DocumentFileAvi => "document-file-avi",
+    DocumentFileBmp => "document-file-bmp",
+    DocumentFileC => "document-file-c",
+    DocumentFileCpp => "document-file-cpp",
+    DocumentFileCss => "document-file-css",
+    DocumentFileDat => "document-file-dat",
+    DocumentFileDmg => "document-file-dmg",
+    DocumentFileDoc => "document-file-doc",
+    DocumentFileDocx => "document-file-docx",
+    DocumentFileDot => "document-file-dot",
+    DocumentFileDotx => "document-file-dotx",
+    DocumentFileDwg => "document-file-dwg",
+    DocumentFileDxf => "document-file-dxf",
+    DocumentFileEps => "document-file-eps",
+    DocumentFileExe => "document-file-exe",
+    DocumentFileFlv => "document-file-flv",
+    DocumentFileGif => "document-file-gif",
+    DocumentFileH => "document-file-h",
+    DocumentFileHtml => "document-file-html",
+    DocumentFileIcs => "document-file-ics",
+    DocumentFileIso => "document-file-iso",
+    DocumentFileJava => "document-file-java",
+    DocumentFileJpg => "document-file-jpg",
+    DocumentFileKey => "document-file-key",
+    DocumentFileM4v => "document-file-m4v",
+    DocumentFileMid => "document-file-mid",
+    DocumentFileMov => "document-file-mov",
+    DocumentFileMp3 => "document-file-mp3",
+    DocumentFileMp4 => "document-file-mp4",
+    DocumentFileMpg => "document-file-mpg",
+    DocumentFileNumbers => "document-file-numbers",
+    DocumentFileOdp => "document-file-odp",
+    DocumentFileOds => "document-file-ods",
+    DocumentFileOdt => "document-file-odt",
+    DocumentFileOts => "document-file-ots",
+    DocumentFileOtt => "document-file-ott",
+    DocumentFilePages => "document-file-pages",
+    DocumentFilePdf => "document-file-pdf",
+    DocumentFilePhp => "document-file-php",
+    DocumentFilePng => "document-file-png",
+    DocumentFilePps => "document-file-pps",
+    DocumentFilePpt => "document-file-ppt",
+    DocumentFilePsd => "document-file-psd",
+    DocumentFilePy => "document-file-py",
+    DocumentFileQt => "document-file-qt",
+    DocumentFileRar => "document-file-rar",
+    DocumentFileRb => "document-file-rb",
+    DocumentFileRtf => "document-file-rtf",
+    DocumentFileSql => "document-file-sql",
+    DocumentFileTga => "document-file-tga",
+    DocumentFileTgz => "document-file-tgz",
+    DocumentFileTiff => "document-file-tiff",
+    DocumentFileTxt => "document-file-txt",
+    DocumentFileWav => "document-file-wav",
+    DocumentFileXls => "document-file-xls",
+    DocumentFileXlsx => "document-file-xlsx",
+    DocumentFileXml => "document-file-xml",
+    DocumentFileYml => "document-file-yml",
+    DocumentFileZip => "document-file-zip",
+    Dollar2 => "dollar2",
+    Dollar => "dollar",
+    Dollars => "dollars",
+    Done => "done",
+    Download2 => "download2",
+    Download => "download",
+    Drafts => "drafts",
+    Dribbble => "dribbble",
+    DriveEta => "drive-eta",
+    Drive2 => "drive2",
+    Drive => "drive",
+    Dropbox => "dropbox",
+    Earth2 => "earth2",
+    Earth => "earth",
+    Eject => "eject",
+    Embed2 => "embed2",
+    Embed => "embed",
+    Enlarge2 => "enlarge2",
+    Enlarge => "enlarge",
+    Enter => "enter",
+    Envelop => "envelop",
+    EqualizerV => "equalizer-v",
+    Equalizer2 => "equalizer2",
+    Equalizer => "equalizer",
+    Eur => "eur",
+    EvStation => "ev-station",
+    EventAvailable => "event-available",
+    EventBusy => "event-busy",
+    Evernote => "evernote",
+    Exit => "exit",
+    ExpandLess => "expand-less",
+    ExpandMore => "expand-more",
+    Eye => "eye",
+    Eyedropper => "eyedropper",
+    Facebook2 => "facebook2",
+    Facebook => "facebook",
+    Fahrenheit => "fahrenheit",
+    Favorite => "favorite",
+    FeaturedPlayList => "featured-play-list",
+    FeaturedVideo => "featured-video",
+    Feed3 => "feed3",
+    Feed => "feed",
+    Female => "female",
+    FileArchive => "file-archive",
+    FileAudio => "file-audio",
+    FileBinary => "file-binary",
+    FileCode => "file-code",
+    FileDownload => "file-download",
+    FileEmpty => "file-empty",
+    FileExcel => "file-excel",
+    FileImage => "file-image",
+    FileMovie => "file-movie",
+    FileMusic => "file-music",
+    FileOpenoffice => "file-openoffice",
+    FilePdf => "file-pdf",
+    FilePicture => "file-picture",
+    FilePlay => "file-play",
+    FilePowerpoint => "file-powerpoint",
+    FileText => "file-text",
+    FileUpload => "file-upload",
+    FileVideo => "file-video",
+    FileWord => "file-word",
+    FileZip => "file-zip",
+    FilesEmpty => "files-empty",
+    Film => "film",
+    Filter => "filter",
+    Finder => "finder",
+    Fingerprint => "fingerprint",
+    FireExtinguisher => "fire-extinguisher",
+    Fire => "fire",
+    Firefox => "firefox",
+    First => "first",
+    Flag => "flag",
+    FlashAuto => "flash-auto",
+    FlashOff => "flash-off",
+    FlashOn => "flash-on",
+    Flattr => "flattr",
+    FloppyDisk => "floppy-disk",
+    Florist => "florist",
+    FlowBranch => "flow-branch",
+    FlowCascade => "flow-cascade",
+    FlowLine => "flow-line",
+    FlowParallel => "flow-parallel",
+    FlowTree => "flow-tree",
+    FolderDownload => "folder-download",
+    FolderMinus => "folder-minus",
+    FolderOpen2 => "folder-open2",
+    FolderOpen => "folder-open",
+    FolderPlus => "folder-plus",
+    FolderShared => "folder-shared",
+    FolderSpecial2 => "folder-special2",
+    FolderSpecial => "folder-special",
+    FolderUpload => "folder-upload",
+    Folder2 => "folder2",
+    Folder => "folder",
+    Fonticons => "fonticons",
+    Forward => "forward",
+    Foursquare => "foursquare",
+    Gamepad => "gamepad",
+    GasStation => "gas-station",
+    Gbp => "gbp",
+    Gift => "gift",
+    Git => "git",
+    Github => "github",
+    Gitlab => "gitlab",
+    Goat => "goat",
+    GooglePlus2 => "google-plus2",
+    GooglePlus => "google-plus",
+    GoogleWallet => "google-wallet",
+    Google => "google",
+    Grav => "grav",
+    Hammer => "hammer",
+    Handshake => "handshake",
+    HdrOff => "hdr-off",
+    HdrOn => "hdr-on",
+    Headphones => "headphones",
+    HeartBroken => "heart-broken",
+    Heart => "heart",
+    Heartbeat => "heartbeat",
+    Help => "help",
+    History => "history",
+    Home => "home",
+    Hotel => "hotel",
+    HourGlass => "hour-glass",
+    Html5 => "html5",
+    Http => "http",
+    Ie => "ie",
+    Image => "image",
+    Images => "images",
+    Imdb => "imdb",
+    ImportContacts => "import-contacts",
+    ImportExport => "import-export",
+    Inbox => "inbox",
+    IndentDecrease => "indent-decrease",
+    IndentIncrease => "indent-increase",
+    Infinite => "infinite",
+    Info => "info",
+    Injection => "injection",
+    Inr => "inr",
+    InsertDriveFile => "insert-drive-file",
+    InsertTemplate => "insert-template",
+    Instagram => "instagram",
+    Install => "install",
+    Italic => "italic",
+    Joomla => "joomla",
+    Jsfiddle => "jsfiddle",
+    Justice => "justice",
+    Key => "key",
+    KeyboardHide => "keyboard-hide",
+    KeyboardReturn => "keyboard-return",
+    KeyboardTab => "keyboard-tab",
+    KeyboardVoice => "keyboard-voice",
+    Keyboard => "keyboard",
+    Krw => "krw",
+    Lab => "lab",
+    Lamp => "lamp",
+    Language => "language",
+    Laptop => "laptop",
+    Last => "last",
+    Lastfm => "lastfm",
+    LayersClear => "layers-clear",
+    Layers => "layers",
+    Leanpub => "leanpub",
+    Library => "library",
+    Libreoffice => "libreoffice",
+    Lightning2 => "lightning2",
+    Lightning3 => "lightning3",
+    Lightning4 => "lightning4",
+    Lightning5 => "lightning5",
+    Lightning => "lightning",
+    Lines => "lines",
+    Link => "link",
+    Linkedin => "linkedin",
+    Linux => "linux",
+    ListNumbered => "list-numbered",
+    List2 => "list2",
+    List => "list",
+    LocalAirport => "local-airport",
+    LocalService => "local-service",
+    LocationCity => "location-city",
+    LocationOff => "location-off",
+    Location => "location",
+    LockRotation => "lock-rotation",
+    Lock => "lock",
+    Looks => "looks",
+    Loop2 => "loop2",
+    Loop => "loop",
+    Ltr => "ltr",
+    MagicWand => "magic-wand",
+    MailRead => "mail-read",
+    Mail => "mail",
+    Male => "male",
+    Map2 => "map2",
+    Map => "map",
+    Mars => "mars",
+    Mastercard => "mastercard",
+    Medal => "medal",
+    Medium => "medium",
+    Medkit => "medkit",
+    Meetup => "meetup",
+    Menu => "menu",
+    Meter => "meter",
+    Mic => "mic",
+    Microscope => "microscope",
+    Minus => "minus",
+    Mobile => "mobile",
+    Money => "money",
+    Moon2 => "moon2",
+    Moon => "moon",
+    MoreHoriz => "more-horiz",
+    MoreVert => "more-vert",
+    Motorcycle => "motorcycle",
+    MoveDown => "move-down",
+    MoveUp => "move-up",
+    MultitrackAudio => "multitrack-audio",
+    Music => "music",
+    MyLocation => "my-location",
+    Navigation => "navigation",
+    NearMe => "near-me",
+    NetworkCell => "network-cell",
+    NetworkLocked => "network-locked",
+    NetworkWifi => "network-wifi",
+    News => "news",
+    Next => "next",
+    None => "none",
+    Not => "not",
+    NoteAdd => "note-add",
+    Note => "note",
+    Notification => "notification",
+    Odnoklassniki => "odnoklassniki",
+    Onedrive => "onedrive",
+    OpenBook => "open-book",
+    Opencart => "opencart",
+    Openid => "openid",
+    Opera => "opera",
+    Organization => "organization",
+    PageBreak => "page-break",
+    Paint => "paint",
+    Palette => "palette",
+    PanTool => "pan-tool",
+    PaperPlane => "paper-plane",
+    ParagraphCenter => "paragraph-center",
+    ParagraphJustify => "paragraph-justify",
+    ParagraphLeft => "paragraph-left",
+    ParagraphRight => "paragraph-right",
+    Paste => "paste",
+    Pause => "pause",
+    Paw => "paw",
+    Paypal => "paypal",
+    Pencil => "pencil",
+    PermPhoneMsg => "perm-phone-msg",
+    PhoneBt => "phone-bt",
+    PhoneForwarded => "phone-forwarded",
+    PhoneInTalk => "phone-in-talk",
+    PhoneLocked => "phone-locked",
+    PhoneMissed => "phone-missed",
+    PhonePaused => "phone-paused",
+    Phone => "phone",
+    PhonelinkErase => "phonelink-erase",
+    PhonelinkLock => "phonelink-lock",
+    PhonelinkOff => "phonelink-off",
+    PhonelinkRing => "phonelink-ring",
+    PhonelinkSetup => "phonelink-setup",
+    Phonelink => "phonelink",
+    PhotoCameraEnhance => "photo-camera-enhance",
+    PhotoCamera => "photo-camera",
+    Piano => "piano",
+    Picassa => "picassa",
+    Pilcrow => "pilcrow",
+    Pin => "pin",
+    Pinterest => "pinterest",
+    Play => "play",
+    Plus => "plus",
+    PowerCord => "power-cord",
+    Power => "power",
+    PresentAll => "present-all",
+    Previous => "previous",
+    Print => "print",
+    Printer => "printer",
+    Profile => "profile",
+    Qa => "qa",
+    Qrcode => "qrcode",
+    Question => "question",
+    Quote => "quote",
+    Rainy2 => "rainy2",
+    Rainy3 => "rainy3",
+    Rainy4 => "rainy4",
+    Rainy => "rainy",
+    Reddit => "reddit",
+    Redo => "redo",
+    Refresh => "refresh",
+    Registered => "registered",
+    Replay => "replay",
+    Reply => "reply",
+    Restaurant => "restaurant",
+    RingVolume => "ring-volume",
+    Rocket => "rocket",
+    Room => "room",
+    Rotation => "rotation",
+    Rouble => "rouble",
+    Rtl => "rtl",
+    Safari => "safari",
+    Satellite => "satellite",
+    School => "school",
+    SdCard => "sd-card",
+    Search => "search",
+    Section => "section",
+    Security => "security",
+    Server => "server",
+    SettingsEthernet => "settings-ethernet",
+    SettingsPhone => "settings-phone",
+    SettingsPower => "settings-power",
+    SettingsVoice => "settings-voice",
+    Share => "share",
+    Shareable => "shareable",
+    Shift => "shift",
+    Ship => "ship",
+    Shit => "shit",
+    Shop => "shop",
+    ShoppingBasket2 => "shopping-basket2",
+    ShoppingBasket => "shopping-basket",
+    Shrink2 => "shrink2",
+    Shrink => "shrink",
+    Shuffle => "shuffle",
+    Skype => "skype",
+    Snowflake => "snowflake",
+    Snowy2 => "snowy2",
+    Snowy3 => "snowy3",
+    Snowy4 => "snowy4",
+    Snowy5 => "snowy5",
+    Snowy => "snowy",
+    SortAsc => "sort-asc",
+    SortDesc => "sort-desc",
+    Soundcloud => "soundcloud",
+    SpaceShuttle => "space-shuttle",
+    SpellCheck => "spell-check",
+    Spinner1 => "spinner1",
+    Spinner2 => "spinner2",
+    Spinner3 => "spinner3",
+    Spinner4 => "spinner4",
+    Spinner5 => "spinner5",
+    Spinner => "spinner",
+    SpoonFork => "spoon-fork",
+    Squirrel => "squirrel",
+    Stack2 => "stack2",
+    Stack3 => "stack3",
+    Stack => "stack",
+    Stackoverflow => "stackoverflow",
+    StarEmpty => "star-empty",
+    StarFull => "star-full",
+    StarHalf => "star-half",
+    Steam2 => "steam2",
+    Steam => "steam",
+    Steps => "steps",
+    Stethoscope => "stethoscope",
+    Stop => "stop",
+    Strikethrough => "strikethrough",
+    Stumbleupon => "stumbleupon",
+    Subscript => "subscript",
+    Subway => "subway",
+    Suitcase => "suitcase",
+    Sun3 => "sun3",
+    Sun4 => "sun4",
+    Sun => "sun",
+    Sunrise => "sunrise",
+    Superscript => "superscript",
+    Swarm => "swarm",
+    Switch => "switch",
+    SyncDisabled => "sync-disabled",
+    SyncProblem => "sync-problem",
+    Tab => "tab",
+    Table => "table",
+    TabletLandscape => "tablet-landscape",
+    Tablet => "tablet",
+    Tag => "tag",
+    Tags => "tags",
+    Target => "target",
+    Themeisle => "themeisle",
+    Thermometer2 => "thermometer2",
+    Thermometer => "thermometer",
+    ThumbsDown => "thumbs-down",
+    ThumbsUp => "thumbs-up",
+    Tools => "tools",
+    Trademark => "trademark",
+    Traff => "traff",
+    TrafficCone => "traffic-cone",
+    Train => "train",
+    Tree => "tree",
+    Trophy => "trophy",
+    Truck => "truck",
+    Try => "try",
+    Tux => "tux",
+    Twitch => "twitch",
+    Twitter => "twitter",
+    Underline => "underline",
+    Undo => "undo",
+    UnfoldLess => "unfold-less",
+    UnfoldMore => "unfold-more",
+    Uninstall => "uninstall",
+    Unlink => "unlink",
+    Unlock => "unlock",
+    Upload2 => "upload2",
+    Upload => "upload",
+    Usb => "usb",
+    UserCheck => "user-check",
+    UserMd => "user-md",
+    UserMinus => "user-minus",
+    UserPlus => "user-plus",
+    UserSecret => "user-secret",
+    User => "user",
+    Users => "users",
+    Venus => "venus",
+    Verified => "verified",
+    Versions => "versions",
+    VerticalAlignBottom => "vertical-align-bottom",
+    VerticalAlignCenter => "vertical-align-center",
+    VerticalAlignTop => "vertical-align-top",
+    VideoCamera => "video-camera",
+    Vimeo => "vimeo",
+    Visa => "visa",
+    Vk => "vk",
+    Voicemail => "voicemail",
+    VolumeHigh => "volume-high",
+    VolumeLow => "volume-low",
+    VolumeMedium => "volume-medium",
+    VolumeMinus => "volume-minus",
+    VolumeMute2 => "volume-mute2",
+    VolumeMute => "volume-mute",
+    VolumePlus => "volume-plus",
+    VpnLock => "vpn-lock",
+    VpnPubl => "vpn-publ",
+    Warning => "warning",
+    Watch => "watch",
+    Water => "water",
+    Weather2 => "weather2",
+    Weather3 => "weather3",
+    Weather4 => "weather4",
+    Weather5 => "weather5",
+    Weather => "weather",
+    Whatsapp => "whatsapp",
+    Widgets => "widgets",
+    WifiConnect => "wifi-connect",
+    WifiFull => "wifi-full",
+    WifiLock => "wifi-lock",
+    WifiLow => "wifi-low",
+    WifiMid => "wifi-mid",
+    WifiOff => "wifi-off",
+    Wikipedia => "wikipedia",
+    Wind => "wind",
+    Windows => "windows",
+    Windy2 => "windy2",
+    Windy3 => "windy3",
+    Windy4 => "windy4",
+    Windy5 => "windy5",
+    Windy => "windy",
+    Wordpress => "wordpress",
+    Wrench => "wrench",
+    Yelp => "yelp",
+    YoutubePlay => "youtube-play",
+    Youtube2 => "youtube2",
+    Youtube => "youtube",
+    ZoomIn => "zoom-in",
+    ZoomOut => "zoom-out",
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(Icon::MifIcon(MifIcon::FloppyDisk).render().to_string(), r#"<span class="mif-floppy-disk icon"></span>"#);
+        assert_eq!(MifIcon::Rotation3d.name(), "3d-rotation");
+    }
+
+    #[test]
+    fn test_file_icons() {
+        assert_eq!(Icon::for_file("src/main.rs").mif_icon(), MifIcon::FileCode);
+        assert_eq!(Icon::for_file("Report.PDF").mif_icon(), MifIcon::FilePdf);
+        assert_eq!(Icon::for_file("README").mif_icon(), MifIcon::FileEmpty);
+        assert_eq!(Icon::FileIcon("zip".into()).mif_icon(), MifIcon::FileZip);
+    }
 }
@@ -1,10 +1,14 @@
+use crate::backend::{Backend, WindowBackend};
 use crate::component::Component;
-use crate::event::{Event, EventBroker};
-use log::{debug, info};
+use crate::dump::HtmlDump;
+use crate::handle::{AppHandle, Posted};
+use crate::event::{Envelope, Event, EventBroker, EventValue, PROTOCOL_VERSION};
+use crate::html::Node;
+use crate::vdom::Document;
+use crate::window::WindowOptions;
+use log::{debug, error, info};
 use std::error::Error;
-use std::fs::{remove_file, File};
-use std::io::Write;
-use web_view::{Content, WebView};
+use web_view::Content;
 
 #[cfg(feature = "use-local-server")]
 use port_check::free_local_port;
@@ -13,14 +17,26 @@ use tiny_http::{Header, Response, Server, StatusCode};
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::any::Any;
-use crate::AppError::NoAppContentError;
+use crate::AppError::{
+    AppClosedError, HtmlDumpError, InvalidMessageError, NoAppContentError, UnknownComponentError,
+    UnsupportedVersionError,
+};
 use std::fmt;
+use std::path::PathBuf;
 
+pub mod backend;
 pub mod component;
+pub mod dump;
+pub mod escape;
 pub mod event;
+pub mod handle;
 pub mod icons;
+pub mod testing;
 pub mod utils;
+pub mod vdom;
+pub mod window;
 pub mod frp;
+pub mod html;
 
 #[cfg(debug_assertions)]
 const METRO_JS: &str = include_str!("www/js/metro.js");
@@ -31,24 +47,60 @@ const METRO_CSS: &str = include_str!("www/css/metro-all.css");
 #[cfg(not(debug_assertions))]
 const METRO_CSS: &str = include_str!("www/css/metro-all.min.css");
 
+/// The id used for events concerning the whole app.
+const APP_ID: &str = "App";
+
+/// Callback for errors occurring while the app is running.
+type ErrorHandler = Box<dyn Fn(&mut dyn Backend, &AppError)>;
+
 /// The main application
 #[derive(Clone)]
 pub struct App {
     title: String,
+    window_options: Rc<RefCell<WindowOptions>>,
+    html_dump: Rc<RefCell<Option<HtmlDump>>>,
     content: Rc<RefCell<Option<Box<dyn Component>>>>,
     event_broker: Rc<RefCell<EventBroker>>,
+    document: Rc<RefCell<Document>>,
+    error_handler: Rc<RefCell<Option<ErrorHandler>>>,
+    handle: AppHandle,
 }
 
 /// Errors to be returned from app functions
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum AppError {
     NoAppContentError,
+    /// A message from the page could not be parsed.
+    InvalidMessageError { payload: String, reason: String },
+    /// A message from the page uses a protocol version this app does not understand.
+    UnsupportedVersionError { payload: String, version: u32 },
+    /// An event has been fired for an id no component owns.
+    UnknownComponentError { id: String },
+    /// The HTML of the page could not be dumped.
+    HtmlDumpError { path: PathBuf, reason: String },
+    /// Work has been posted to an app whose window has been closed.
+    AppClosedError,
 }
 
 impl Error for AppError {}
 impl fmt::Display for AppError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "No content defined for this app.")
+        match self {
+            NoAppContentError => write!(f, "No content defined for this app."),
+            InvalidMessageError { payload, reason } => {
+                write!(f, "Invalid message {} received: {}", payload, reason)
+            }
+            UnsupportedVersionError { payload, version } => write!(
+                f,
+                "Message {} uses version {}, only version {} is supported.",
+                payload, version, PROTOCOL_VERSION
+            ),
+            UnknownComponentError { id } => write!(f, "No component with ID {}.", id),
+            HtmlDumpError { path, reason } => {
+                write!(f, "Could not dump HTML to {}: {}", path.display(), reason)
+            }
+            AppClosedError => write!(f, "The window of the app has been closed."),
+        }
     }
 }
 
@@ -57,8 +109,32 @@ impl App {
     pub fn new(title: impl Into<String>) -> Self {
         App {
             title: title.into(),
+            window_options: Rc::new(RefCell::new(WindowOptions::default())),
+            html_dump: Rc::new(RefCell::new(None)),
             content: Rc::new(RefCell::new(None)),
             event_broker: Rc::new(RefCell::new(EventBroker::new())),
+            document: Rc::new(RefCell::new(Document::new())),
+            error_handler: Rc::new(RefCell::new(None)),
+            handle: AppHandle::new(),
+        }
+    }
+
+    /// Set the options for the window of the app.
+    pub fn set_window_options(&self, options: WindowOptions) {
+        self.window_options.replace(options);
+    }
+
+    /// Dump the HTML of the page to a file for debugging. Nothing is dumped by default.
+    pub fn set_html_dump(&self, dump: Option<HtmlDump>) {
+        self.html_dump.replace(dump);
+    }
+
+    /// Request the current DOM of the page to be written to the HTML dump.
+    pub fn dump_dom(&self, backend: &mut dyn Backend) {
+        if self.html_dump.borrow().is_some() {
+            if let Err(eval_error) = backend.eval("dump_dom()") {
+                error!("Could not request DOM dump: {}", eval_error);
+            }
         }
     }
 
@@ -68,62 +144,205 @@ impl App {
     }
 
     /// Send an event to all components of the app.
-    pub fn send<E: Any>(&self, webview: &mut WebView<()>, event: &E) {
-        self.event_broker.borrow().send(webview, event)
+    pub fn send<E: Any>(&self, backend: &mut dyn Backend, event: &E) {
+        self.event_broker.borrow().send(backend, event)
     }
 
     /// Subscribe to an event.
-    pub fn subscribe<F: Fn(&mut WebView<()>, &E) + 'static, E: Any>(&self, listener: F) {
+    pub fn subscribe<F: Fn(&mut dyn Backend, &E) + 'static, E: Any>(&self, listener: F) {
         self.event_broker.borrow_mut().subscribe(listener)
     }
 
-    fn build_html(&mut self) -> Result<String, Box<dyn Error>> {
+    /// A handle for other threads to post work to the UI thread of the app.
+    pub fn handle(&self) -> AppHandle {
+        self.handle.clone()
+    }
+
+    /// Set a callback for errors occurring while the app is running,
+    /// e.g. invalid messages from the page. Errors are always logged.
+    pub fn set_error_handler(&self, handler: impl Fn(&mut dyn Backend, &AppError) + 'static) {
+        self.error_handler.borrow_mut().replace(Box::new(handler));
+    }
+
+    fn report_error(&self, backend: &mut dyn Backend, app_error: AppError) {
+        error!("{}", app_error);
+        if let Some(handler) = self.error_handler.borrow().as_ref() {
+            handler(backend, &app_error);
+        }
+    }
+
+    /// Parse a message sent from the page.
+    fn parse_message(message: &str) -> Result<Event, AppError> {
+        let invalid = |reason: serde_json::Error| InvalidMessageError {
+            payload: message.into(),
+            reason: reason.to_string(),
+        };
+
+        let envelope: Envelope = serde_json::from_str(message).map_err(invalid)?;
+        if envelope.version != PROTOCOL_VERSION {
+            return Err(UnsupportedVersionError {
+                payload: message.into(),
+                version: envelope.version,
+            });
+        }
+        serde_json::from_value(envelope.event).map_err(invalid)
+    }
+
+    /// Handle a raw message sent from the page.
+    pub(crate) fn handle_message(&self, backend: &mut dyn Backend, message: &str) {
+        match App::parse_message(message) {
+            Ok(event) => self.handle_event(backend, &event),
+            Err(app_error) => self.report_error(backend, app_error),
+        }
+    }
+
+    /// Render the main content of the app.
+    pub(crate) fn render_content(&self) -> Result<Node, Box<dyn Error>> {
         let mut content = self.content.borrow_mut();
 
         if content.is_none() {
             return Err(Box::new(NoAppContentError));
         }
 
-        let html = format!(
+        Ok(content.as_mut().unwrap().render())
+    }
+
+    /// Embed the rendered content into the complete page.
+    pub(crate) fn page_html(&self, content: &Node) -> String {
+        format!(
             include_str!("www/html/app.html"),
             eventjs = include_str!("www/js/event.js"),
             metrojs = METRO_JS,
             metrocss = METRO_CSS,
             denshicss = include_str!("www/css/denshi.css"),
-            content = content.as_mut().unwrap().render()
-        );
+            windowcss = self.window_options.borrow().css(),
+            content = content
+        )
+    }
 
-        if cfg!(debug_assertions) {
-            let _result = remove_file("test.html");
-            let mut file = File::create("test.html")?;
-            file.write_all(html.as_bytes())?;
+    /// Render the complete page and write it to the HTML dump if configured.
+    pub(crate) fn render_page(&self) -> Result<(Node, String), Box<dyn Error>> {
+        let content = self.render_content()?;
+        let html = self.page_html(&content);
+        if let Some(dump) = &*self.html_dump.borrow() {
+            dump.write(&html)?;
         }
+        Ok((content, html))
+    }
 
+    fn build_html(&mut self) -> Result<String, Box<dyn Error>> {
+        let (content, html) = self.render_page()?;
+        self.document.borrow_mut().set_content(content);
         Ok(html)
     }
 
+    /// Pass an event coming from the page to the component owning its id.
+    ///
+    /// Without a document to look up the owner the event is passed to the whole content.
+    pub(crate) fn handle_event(&self, backend: &mut dyn Backend, event: &Event) {
+        debug!("Received event {:?}", event);
+        if event.id == APP_ID {
+            self.handle_app_event(backend, event);
+            return;
+        }
+
+        let handler = match backend.document() {
+            Some(document) => document.handler(&event.id),
+            None => {
+                if let Some(content) = self.content.borrow_mut().as_mut() {
+                    content.handle_event(backend, event);
+                }
+                return;
+            }
+        };
+
+        match handler {
+            Some(handler) => handler(backend, event),
+            None => self.report_error(backend, UnknownComponentError { id: event.id.clone() }),
+        }
+        Self::run_update_hooks(backend);
+
+        if matches!(&*self.html_dump.borrow(), Some(dump) if dump.is_after_events()) {
+            self.dump_dom(backend);
+        }
+    }
+
+    /// Bring all elements with update hooks up to date.
+    fn run_update_hooks(backend: &mut dyn Backend) {
+        let ids = match backend.document() {
+            Some(document) => document.update_hook_ids(),
+            None => return,
+        };
+        for id in ids {
+            // a previous hook may have replaced the element
+            let hook = backend.document().and_then(|document| document.update_hook(&id));
+            if let Some(hook) = hook {
+                if let Err(update_error) = hook(backend) {
+                    error!("Could not update {}: {}", id, update_error);
+                }
+            }
+        }
+    }
+
+    /// Handle events concerning the whole app.
+    fn handle_app_event(&self, backend: &mut dyn Backend, event: &Event) {
+        match &event.value {
+            EventValue::DomDumped(html) => {
+                let written = self.html_dump.borrow().as_ref().map(|dump| dump.write(html));
+                if let Some(Err(app_error)) = written {
+                    self.report_error(backend, app_error);
+                }
+            }
+            EventValue::Posted => self.deliver_posted(backend),
+            _ => {}
+        }
+    }
+
+    /// Run the closures and send the messages posted by other threads.
+    fn deliver_posted(&self, backend: &mut dyn Backend) {
+        for posted in self.handle.take_posted() {
+            match posted {
+                Posted::Closure(f) => f(backend),
+                Posted::Message(message) => self.event_broker.borrow().send_any(backend, &*message),
+            }
+        }
+        Self::run_update_hooks(backend);
+    }
+
     fn run_web_view(&mut self, content_str: Content<String>) -> Result<(), Box<dyn Error>> {
         let ref title = self.title.clone();
-        let mut content = self.content.borrow_mut();
 
-        if content.is_none() {
+        if self.content.borrow().is_none() {
             return Err(Box::new(NoAppContentError));
         }
 
-        web_view::builder()
+        let options = self.window_options.borrow().clone();
+        let (width, height) = options.initial_size();
+
+        let mut webview = web_view::builder()
             .content(content_str)
-            .size(800, 600)
-            .resizable(true)
-            .debug(true)
+            .size(width, height)
+            .resizable(options.resizable)
+            .frameless(options.frameless)
+            .debug(options.debug)
             .user_data(())
             .invoke_handler(|webview, arg| {
-                let event: Event = serde_json::from_str(arg).unwrap();
-                debug!("Received event {:?}", &event);
-                content.as_mut().unwrap().handle_event(webview, &event);
+                let mut document = self.document.borrow_mut();
+                self.handle_message(&mut WindowBackend { webview, document: &mut document }, arg);
                 Ok(())
             })
             .title(title.as_str())
-            .run()?;
+            .build()?;
+
+        self.handle.attach(webview.handle());
+        if let Some(color) = options.background_color {
+            webview.set_color(color);
+        }
+        if options.fullscreen {
+            webview.set_fullscreen(true);
+        }
+
+        webview.run()?;
         Ok(())
     }
 
@@ -0,0 +1,415 @@
+use crate::backend::{Backend, RecordingBackend};
+use crate::event::{Event, EventValue};
+use crate::html::Node;
+use crate::App;
+use std::any::Any;
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+/// Drives an app without a window.
+///
+/// The content of the app is rendered once on creation. Afterwards events can be
+/// injected as if they were coming from the page and the JavaScript evaluated as
+/// a reaction can be inspected.
+pub struct TestApp {
+    app: App,
+    content: Node,
+    html: String,
+    backend: RecordingBackend,
+}
+
+impl TestApp {
+    /// Render the content of the app and prepare it for receiving events.
+    pub fn new(app: &App) -> Result<Self, Box<dyn Error>> {
+        let (content, html) = app.render_page()?;
+        let mut backend = RecordingBackend::new();
+        backend.document().unwrap().set_content(content.clone());
+        Ok(TestApp {
+            app: app.clone(),
+            html,
+            content,
+            backend,
+        })
+    }
+
+    /// The backend the app is talking to, e.g. for refreshing components.
+    pub fn backend(&mut self) -> &mut RecordingBackend {
+        &mut self.backend
+    }
+
+    /// The content of the app as rendered on creation.
+    pub fn content(&self) -> &Node {
+        &self.content
+    }
+
+    /// The complete page as rendered on creation.
+    pub fn html(&self) -> &str {
+        &self.html
+    }
+
+    /// Inject an event for a component.
+    pub fn fire(&mut self, id: impl Into<String>, value: EventValue) {
+        self.fire_event(&Event {
+            id: id.into(),
+            value,
+        });
+    }
+
+    /// Inject an already built event.
+    pub fn fire_event(&mut self, event: &Event) {
+        self.app.handle_event(&mut self.backend, event);
+    }
+
+    /// Inject a raw message as sent by the page.
+    pub fn send_message(&mut self, message: &str) {
+        self.app.handle_message(&mut self.backend, message);
+    }
+
+    /// Simulate a click on a component.
+    pub fn click(&mut self, id: impl Into<String>) {
+        self.fire(id, EventValue::Clicked);
+    }
+
+    /// Simulate the user changing the value of an input.
+    pub fn change_value(&mut self, id: impl Into<String>, value: impl Into<String>) {
+        self.fire(id, EventValue::ValueChanged(value.into()));
+    }
+
+    /// Send an event to all subscribers of the app.
+    pub fn send<E: Any>(&mut self, event: &E) {
+        self.app.send(&mut self.backend, event);
+    }
+
+    /// Record all events of the given type sent through the app from now on.
+    pub fn record<E: Any + Clone>(&self) -> Rc<RefCell<Vec<E>>> {
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        let sent_clone = sent.clone();
+        self.app.subscribe(move |_backend, event: &E| {
+            sent_clone.borrow_mut().push(event.clone());
+        });
+        sent
+    }
+
+    /// All scripts evaluated so far.
+    pub fn scripts(&self) -> &[String] {
+        self.backend.scripts()
+    }
+
+    /// Return all scripts evaluated so far and forget them.
+    pub fn take_scripts(&mut self) -> Vec<String> {
+        self.backend.take_scripts()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::button::{Button, Checkbox};
+    use crate::component::layout::Form;
+    use crate::component::text::{TextArea, TextField};
+    use crate::component::tree::{Tree, TreeModel};
+    use crate::component::Component;
+    use crate::AppError;
+    use crate::dump::HtmlDump;
+    use crate::frp::{self, lift2};
+    use enclose::enclose;
+    use std::cell::Cell;
+
+    struct LetterTree {}
+
+    impl TreeModel<char> for LetterTree {
+        fn roots(&self) -> Vec<char> {
+            vec!['a', 'b']
+        }
+
+        fn children(&self, _parent: &char) -> Vec<char> {
+            Vec::new()
+        }
+
+        fn caption(&self, node: &char) -> String {
+            node.to_string()
+        }
+
+        fn has_children(&self, _node: &char) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_no_content() {
+        let app = App::new("Test");
+        assert!(TestApp::new(&app).is_err());
+    }
+
+    #[test]
+    fn test_value_changed() {
+        let text = TextField::new("Name");
+        let mut form = Form::new();
+        form.add_line(text.clone());
+
+        let app = App::new("Test");
+        app.set_content(form);
+
+        let mut test_app = TestApp::new(&app).unwrap();
+        assert!(test_app.html().contains(&text.id()));
+        assert_eq!(test_app.content().find_by_id(&text.id()).unwrap().tag(), "input");
+
+        test_app.change_value(text.id(), "Denshi");
+        assert_eq!(text.text().value(), "Denshi");
+        assert!(test_app.scripts().is_empty());
+    }
+
+    #[test]
+    fn test_broker_sends() {
+        let app = App::new("Test");
+        let tree = Tree::new(LetterTree {});
+        tree.set_click_event(enclose!((app) move |backend, letter| {
+            app.send(backend, letter)
+        }));
+        app.set_content(tree.clone());
+
+        let mut test_app = TestApp::new(&app).unwrap();
+        let sent = test_app.record::<char>();
+
+        let node_id = test_app
+            .content()
+            .find(|element| element.tag() == "li")
+            .and_then(|element| element.attribute("id"))
+            .unwrap()
+            .to_string();
+        test_app.fire(tree.id(), EventValue::ChildClicked(node_id));
+
+        assert_eq!(*sent.borrow(), ['a']);
+    }
+
+    #[test]
+    fn test_refresh_patches() {
+        let button = Button::new("Start");
+        let text = TextField::new("Name");
+        let mut form = Form::new();
+        form.add_line(text.clone());
+        form.add_line(button.clone());
+
+        let app = App::new("Test");
+        app.set_content(form.clone());
+        let mut test_app = TestApp::new(&app).unwrap();
+
+        button.set_label("Stop");
+        form.refresh(test_app.backend()).unwrap();
+
+        assert_eq!(test_app.take_scripts(), [format!("set_content('{}', 'Stop')", button.id())]);
+    }
+
+    #[test]
+    fn test_refresh_tree() {
+        let tree = Tree::new(LetterTree {});
+        let mut form = Form::new();
+        form.add_line(tree.clone());
+
+        let app = App::new("Test");
+        app.set_content(form.clone());
+        let mut test_app = TestApp::new(&app).unwrap();
+
+        // the nodes are created anew, Metro has to build the tree view again
+        form.refresh(test_app.backend()).unwrap();
+        let scripts = test_app.take_scripts();
+        assert_eq!(scripts.len(), 1);
+        assert!(scripts[0].starts_with(&format!("replace_element('{}', ", tree.id())));
+    }
+
+    #[test]
+    fn test_bindings() {
+        let name = TextField::new("Name");
+        let agree = Checkbox::new("Agree");
+        let submit = Button::new("Submit");
+        submit.set_label(lift2(&name.text(), &agree.checked(), |name, agree| {
+            if agree { format!("Submit {}", name) } else { "Agree first".into() }
+        }));
+        let mut form = Form::new();
+        form.add_line(name.clone());
+        form.add_line(agree.clone());
+        form.add_line(submit.clone());
+
+        let app = App::new("Test");
+        app.set_content(form);
+        let mut test_app = TestApp::new(&app).unwrap();
+        let clicks = submit.clicks().accumulate(0, |count, _| count + 1);
+
+        test_app.change_value(name.id(), "Joe");
+        test_app.fire(agree.id(), EventValue::CheckedChanged(true));
+        assert!(agree.checked().value());
+        assert_eq!(test_app.take_scripts(), [format!("set_content('{}', 'Submit Joe')", submit.id())]);
+
+        test_app.click(submit.id());
+        test_app.click(submit.id());
+        assert_eq!(clicks.value(), 2);
+        assert!(test_app.scripts().is_empty());
+    }
+
+    #[test]
+    fn test_refresh_changed_only() {
+        let (counts, count_sink) = frp::Event::new_with_sink();
+        let renders = Rc::new(Cell::new(0));
+        let button = Button::new(counts.hold(0).map(enclose!((renders) move |count| {
+            renders.set(renders.get() + 1);
+            format!("{} files", count)
+        })));
+        let text = TextField::new("Name");
+        let mut form = Form::new();
+        form.add_line(text.clone());
+        form.add_line(button.clone());
+
+        let app = App::new("Test");
+        app.set_content(form);
+        let mut test_app = TestApp::new(&app).unwrap();
+        let rendered = renders.get();
+
+        test_app.change_value(text.id(), "Joe");
+        assert_eq!(renders.get(), rendered);
+
+        count_sink.send(&3);
+        test_app.change_value(text.id(), "Ann");
+        assert_eq!(renders.get(), rendered + 1);
+        assert_eq!(test_app.take_scripts(), [format!("set_content('{}', '3 files')", button.id())]);
+    }
+
+    #[test]
+    fn test_text_binding() {
+        let model = Rc::new(RefCell::new("Joe".to_string()));
+        let name = TextField::new("Name");
+        name.bind(model.clone());
+        let notes = TextArea::new();
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        notes.set_change_event(enclose!((changes) move |text| changes.borrow_mut().push(text.to_string())));
+        let note_texts = notes.changes().hold(String::new());
+        let mut form = Form::new();
+        form.add_line(name.clone());
+        form.add_line(notes.clone());
+
+        let app = App::new("Test");
+        app.set_content(form);
+        let mut test_app = TestApp::new(&app).unwrap();
+        assert!(test_app.html().contains(r#"value="Joe""#));
+
+        test_app.change_value(name.id(), "Ann");
+        assert_eq!(*model.borrow(), "Ann");
+        *model.borrow_mut() = "Bob".into();
+        notes.set_text("Loaded");
+        test_app.change_value(notes.id(), "Typed");
+        assert_eq!(*changes.borrow(), ["Typed"]);
+        assert_eq!(note_texts.value(), "Typed");
+        assert_eq!(test_app.take_scripts(), [
+            format!("set_attribute('{}', 'value', 'Bob')", name.id()),
+            format!("set_value('{}', 'Bob')", name.id()),
+        ]);
+
+        notes.set_text("Loaded");
+        notes.clone().refresh(test_app.backend()).unwrap();
+        assert_eq!(test_app.take_scripts(), [format!("set_value('{}', 'Loaded')", notes.id())]);
+    }
+
+    #[test]
+    fn test_routing() {
+        let first = TextField::new("First");
+        let second = TextField::new("Second");
+        let mut form = Form::new();
+        form.add_line(first.clone());
+        form.add_line(second.clone());
+
+        let app = App::new("Test");
+        app.set_content(form.clone());
+        let mut test_app = TestApp::new(&app).unwrap();
+
+        test_app.change_value(second.id(), "2");
+        test_app.change_value("id-unknown", "3");
+        assert_eq!(first.text().value(), "");
+        assert_eq!(second.text().value(), "2");
+
+        // handlers are still registered after the form has been refreshed
+        first.set_label("One");
+        form.refresh(test_app.backend()).unwrap();
+        test_app.change_value(first.id(), "1");
+        assert_eq!(first.text().value(), "1");
+    }
+
+    #[test]
+    fn test_messages() {
+        let text = TextField::new("Name");
+        let app = App::new("Test");
+        app.set_content(text.clone());
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        app.set_error_handler(enclose!((errors) move |_backend, app_error| {
+            errors.borrow_mut().push(app_error.clone());
+        }));
+        let mut test_app = TestApp::new(&app).unwrap();
+
+        let message = format!(r#"{{"version":1,"event":{{"id":"{}","value":{{"ValueChanged":"a"}}}}}}"#, text.id());
+        test_app.send_message(&message);
+        assert_eq!(text.text().value(), "a");
+        assert!(errors.borrow().is_empty());
+
+        test_app.send_message("undefined");
+        test_app.send_message(r#"{"version":2,"event":{}}"#);
+        test_app.send_message(r#"{"version":1,"event":{"id":"x","value":"Unknown"}}"#);
+        test_app.send_message(r#"{"version":1,"event":{"id":"x","value":"Clicked"}}"#);
+        test_app.send_message(r#"{"version":1,"event":{"id":"App","value":"PageLoaded"}}"#);
+
+        let errors = errors.borrow();
+        assert_eq!(errors.len(), 4);
+        assert!(matches!(&errors[0], AppError::InvalidMessageError { payload, .. } if payload == "undefined"));
+        assert!(matches!(&errors[1], AppError::UnsupportedVersionError { version: 2, .. }));
+        assert!(matches!(&errors[2], AppError::InvalidMessageError { reason, .. } if reason.contains("Unknown")));
+        assert_eq!(errors[3], AppError::UnknownComponentError { id: "x".into() });
+    }
+
+    #[test]
+    fn test_html_dump() {
+        let path = std::env::temp_dir().join(format!("denshi-dump-{}.html", std::process::id()));
+        let button = Button::new("Dump");
+        let app = App::new("Test");
+        app.set_content(button.clone());
+        app.set_html_dump(Some(HtmlDump::new(&path).after_events(true)));
+        let mut test_app = TestApp::new(&app).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), test_app.html());
+
+        test_app.take_scripts();
+        test_app.click(button.id());
+        assert_eq!(test_app.scripts().last().map(String::as_str), Some("dump_dom()"));
+
+        test_app.fire("App", EventValue::DomDumped("<html></html>".into()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "<html></html>");
+        std::fs::remove_file(&path).unwrap();
+
+        app.set_html_dump(Some(HtmlDump::new(std::env::temp_dir().join("missing").join("dump.html"))));
+        assert!(TestApp::new(&app).is_err());
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Progress(u32);
+
+    #[test]
+    fn test_app_handle() {
+        let done = Checkbox::new("Done");
+        let app = App::new("Test");
+        app.set_content(done.clone());
+        app.subscribe(enclose!((done) move |_backend, progress: &Progress| done.set_checked(progress.0 == 100)));
+        let mut test_app = TestApp::new(&app).unwrap();
+        let received = test_app.record::<Progress>();
+
+        let handle = app.handle();
+        std::thread::spawn(move || {
+            handle.post(|backend| backend.eval("working()").unwrap()).unwrap();
+            handle.send(Progress(100)).unwrap();
+        })
+        .join()
+        .unwrap();
+        assert!(received.borrow().is_empty());
+
+        test_app.fire("App", EventValue::Posted);
+        assert_eq!(*received.borrow(), [Progress(100)]);
+        assert_eq!(test_app.scripts()[0], "working()");
+        // the checkbox has been updated by its update hook
+        assert!(test_app.scripts().iter().any(|script| script.starts_with("set_checked(")));
+    }
+}
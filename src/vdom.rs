@@ -0,0 +1,536 @@
+use crate::escape::escape_js_string;
+use crate::event::{EventHandler, UpdateHook};
+use crate::html::{Element, Node};
+use std::collections::HashMap;
+
+/// A change to be applied to the live page.
+///
+/// Elements are always addressed by their id, as Metro restructures the markup
+/// below its widgets.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Patch {
+    /// Replace an element (and the container Metro wrapped it in) by new markup.
+    Replace { id: String, html: String },
+    /// Set or change an attribute.
+    SetAttribute { id: String, name: String, value: String },
+    /// Remove an attribute.
+    RemoveAttribute { id: String, name: String },
+    /// Set the current value of an input or text area.
+    SetValue { id: String, value: String },
+    /// Set whether a check box is currently checked.
+    SetChecked { id: String, checked: bool },
+    /// Replace the content of an element without element children.
+    SetContent { id: String, html: String },
+    /// Insert new markup into a parent, before a sibling or at the end.
+    Insert {
+        parent: String,
+        before: Option<String>,
+        html: String,
+    },
+    /// Remove an element.
+    Remove { id: String },
+}
+
+impl Patch {
+    /// The JavaScript applying this patch to the page.
+    pub fn to_js(&self) -> String {
+        match self {
+            Patch::Replace { id, html } => format!(
+                "replace_element('{id}', '{html}')",
+                id = escape_js_string(id),
+                html = escape_js_string(html)
+            ),
+            Patch::SetAttribute { id, name, value } => format!(
+                "set_attribute('{id}', '{name}', '{value}')",
+                id = escape_js_string(id),
+                name = escape_js_string(name),
+                value = escape_js_string(value)
+            ),
+            Patch::RemoveAttribute { id, name } => format!(
+                "remove_attribute('{id}', '{name}')",
+                id = escape_js_string(id),
+                name = escape_js_string(name)
+            ),
+            Patch::SetValue { id, value } => format!(
+                "set_value('{id}', '{value}')",
+                id = escape_js_string(id),
+                value = escape_js_string(value)
+            ),
+            Patch::SetChecked { id, checked } => format!(
+                "set_checked('{id}', {checked})",
+                id = escape_js_string(id),
+                checked = checked
+            ),
+            Patch::SetContent { id, html } => format!(
+                "set_content('{id}', '{html}')",
+                id = escape_js_string(id),
+                html = escape_js_string(html)
+            ),
+            Patch::Insert {
+                parent,
+                before,
+                html,
+            } => format!(
+                "insert_element('{parent}', {before}, '{html}')",
+                parent = escape_js_string(parent),
+                before = match before {
+                    Some(before) => format!("'{}'", escape_js_string(before)),
+                    None => "null".into(),
+                },
+                html = escape_js_string(html)
+            ),
+            Patch::Remove { id } => format!("remove_element('{id}')", id = escape_js_string(id)),
+        }
+    }
+}
+
+/// The page as it was rendered last.
+///
+/// Used to send only the changes of a component to the page when it is refreshed
+/// and to route events to the component owning the id they were fired with.
+#[derive(Default)]
+pub struct Document {
+    content: Option<Node>,
+    callbacks: Callbacks,
+}
+
+/// The callbacks of all elements in the page, by id.
+#[derive(Default)]
+struct Callbacks {
+    handlers: HashMap<String, EventHandler>,
+    updates: HashMap<String, UpdateHook>,
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Document {
+            content: None,
+            callbacks: Callbacks::default(),
+        }
+    }
+
+    /// Remember the completely rendered content of the page.
+    pub fn set_content(&mut self, content: Node) {
+        self.callbacks = Callbacks::default();
+        self.callbacks.register(&content);
+        self.content = Some(content);
+    }
+
+    /// The handler for events fired with the given id.
+    pub fn handler(&self, id: &str) -> Option<EventHandler> {
+        self.callbacks.handlers.get(id).cloned()
+    }
+
+    /// The update hook of the element with the given id.
+    pub fn update_hook(&self, id: &str) -> Option<UpdateHook> {
+        self.callbacks.updates.get(id).cloned()
+    }
+
+    /// The ids of all elements with an update hook, sorted.
+    pub fn update_hook_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.callbacks.updates.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// The content of the page as rendered last.
+    pub fn content(&self) -> Option<&Node> {
+        self.content.as_ref()
+    }
+
+    /// Remember a newly rendered component and return the patches to update the page.
+    pub fn update(&mut self, id: &str, node: Node) -> Vec<Patch> {
+        let old = self.content.as_ref().and_then(|content| content.find_by_id(id));
+        let patches = match old {
+            Some(old) => {
+                self.callbacks.unregister(old);
+                diff(id, old, &node)
+            }
+            None => vec![Patch::Replace {
+                id: id.into(),
+                html: node.to_string(),
+            }],
+        };
+        self.callbacks.register(&node);
+        if let Some(content) = self.content.as_mut() {
+            let _result = content.replace_by_id(id, node);
+        }
+        patches
+    }
+}
+
+impl Callbacks {
+    fn register(&mut self, node: &Node) {
+        if let Node::Element(element) = node {
+            if let Some(id) = element.attribute("id") {
+                if let Some(handler) = element.event_handler() {
+                    self.handlers.insert(id.into(), handler.clone());
+                }
+                if let Some(hook) = element.update_hook() {
+                    self.updates.insert(id.into(), hook.clone());
+                }
+            }
+            for child in element.child_nodes() {
+                self.register(child);
+            }
+        }
+    }
+
+    fn unregister(&mut self, element: &Element) {
+        if let Some(id) = element.attribute("id") {
+            self.handlers.remove(id);
+            self.updates.remove(id);
+        }
+        for child in element.child_nodes() {
+            if let Node::Element(child) = child {
+                self.unregister(child);
+            }
+        }
+    }
+}
+
+/// Calculate the patches turning the element with the given id into a new rendering.
+pub fn diff(id: &str, old: &Element, new: &Node) -> Vec<Patch> {
+    let mut patches = Vec::new();
+    match new {
+        Node::Element(new) if new.attribute("id") == Some(id) => {
+            if diff_element(old, new, &mut patches).is_err() {
+                patches.push(replace(id, new));
+            }
+        }
+        _ => patches.push(Patch::Replace {
+            id: id.into(),
+            html: new.to_string(),
+        }),
+    }
+    patches
+}
+
+fn replace(id: &str, element: &Element) -> Patch {
+    Patch::Replace {
+        id: id.into(),
+        html: element.to_string(),
+    }
+}
+
+/// Diff two elements. An `Err` signals that the changes can only be applied by
+/// replacing the closest element with an id.
+fn diff_element(old: &Element, new: &Element, patches: &mut Vec<Patch>) -> Result<(), ()> {
+    if old.tag() != new.tag() || old.attribute("id") != new.attribute("id") {
+        return Err(());
+    }
+
+    match new.attribute("id") {
+        Some(id) => {
+            let mut element_patches = Vec::new();
+            if diff_attributes(id, old, new, &mut element_patches).is_ok()
+                && diff_children(id, old, new, &mut element_patches).is_ok()
+            {
+                patches.append(&mut element_patches);
+            } else {
+                patches.push(replace(id, new));
+            }
+            Ok(())
+        }
+        None => {
+            if old.attributes() != new.attributes() {
+                return Err(());
+            }
+            diff_child_list(old.child_nodes(), new.child_nodes(), patches)
+        }
+    }
+}
+
+fn diff_attributes(id: &str, old: &Element, new: &Element, patches: &mut Vec<Patch>) -> Result<(), ()> {
+    // Metro reads the data attributes of its widgets only when creating them
+    if old.attribute("data-role").is_some() && data_attributes(old) != data_attributes(new) {
+        return Err(());
+    }
+    for (name, _) in old.attributes() {
+        if new.attribute(name).is_none() {
+            patches.push(Patch::RemoveAttribute {
+                id: id.into(),
+                name: name.clone(),
+            });
+            if name == "checked" && new.tag() == "input" {
+                patches.push(Patch::SetChecked {
+                    id: id.into(),
+                    checked: false,
+                });
+            }
+        }
+    }
+    for (name, value) in new.attributes() {
+        if old.attribute(name) != Some(value) {
+            patches.push(Patch::SetAttribute {
+                id: id.into(),
+                name: name.clone(),
+                value: value.clone(),
+            });
+            // the attribute is only the default, the live value has to be set as well
+            if name == "value" && new.tag() == "input" {
+                patches.push(Patch::SetValue {
+                    id: id.into(),
+                    value: value.clone(),
+                });
+            }
+            if name == "checked" && new.tag() == "input" {
+                patches.push(Patch::SetChecked {
+                    id: id.into(),
+                    checked: true,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Data attributes Metro keeps observing after creating a widget.
+const LIVE_DATA_ATTRIBUTES: [&str; 1] = ["data-indeterminate"];
+
+fn data_attributes(element: &Element) -> Vec<&(String, String)> {
+    element
+        .attributes()
+        .iter()
+        .filter(|(name, _)| name.starts_with("data-") && !LIVE_DATA_ATTRIBUTES.contains(&name.as_str()))
+        .collect()
+}
+
+fn diff_children(id: &str, old: &Element, new: &Element, patches: &mut Vec<Patch>) -> Result<(), ()> {
+    if old.child_nodes() == new.child_nodes() {
+        return Ok(());
+    }
+
+    if !has_element_children(old) && !has_element_children(new) {
+        if new.tag() == "textarea" {
+            patches.push(Patch::SetValue {
+                id: id.into(),
+                value: new.text_content(),
+            });
+        } else {
+            patches.push(Patch::SetContent {
+                id: id.into(),
+                html: new.child_nodes().iter().map(|child| child.to_string()).collect(),
+            });
+        }
+        return Ok(());
+    }
+
+    // Metro builds the children of its widgets itself, e.g. the nodes of a tree view
+    if new.attribute("data-role").is_some() {
+        return Err(());
+    }
+
+    match (child_ids(old), child_ids(new)) {
+        (Some(old_ids), Some(new_ids)) => diff_keyed(id, &old_ids, old, &new_ids, new, patches),
+        _ => diff_child_list(old.child_nodes(), new.child_nodes(), patches),
+    }
+}
+
+fn has_element_children(element: &Element) -> bool {
+    element.child_nodes().iter().any(|child| child.as_element().is_some())
+}
+
+/// The ids of all children if all of them are elements with an id.
+fn child_ids(element: &Element) -> Option<Vec<&str>> {
+    element
+        .child_nodes()
+        .iter()
+        .map(|child| child.as_element().and_then(|e| e.attribute("id")))
+        .collect()
+}
+
+/// Diff children identified by their ids, inserting and removing elements as needed.
+fn diff_keyed(
+    parent: &str,
+    old_ids: &[&str],
+    old: &Element,
+    new_ids: &[&str],
+    new: &Element,
+    patches: &mut Vec<Patch>,
+) -> Result<(), ()> {
+    let old_common: Vec<&str> = old_ids.iter().copied().filter(|id| new_ids.contains(id)).collect();
+    let new_common: Vec<&str> = new_ids.iter().copied().filter(|id| old_ids.contains(id)).collect();
+    if old_common != new_common {
+        // moved elements are not supported
+        return Err(());
+    }
+
+    for id in old_ids {
+        if !new_ids.contains(id) {
+            patches.push(Patch::Remove { id: id.to_string() });
+        }
+    }
+
+    for (index, id) in new_ids.iter().enumerate() {
+        let new_child = new.child_nodes()[index].as_element().unwrap();
+        match old_ids.iter().position(|old_id| old_id == id) {
+            Some(old_index) => {
+                let old_child = old.child_nodes()[old_index].as_element().unwrap();
+                if diff_element(old_child, new_child, patches).is_err() {
+                    patches.push(replace(id, new_child));
+                }
+            }
+            None => patches.push(Patch::Insert {
+                parent: parent.into(),
+                before: new_ids[index + 1..]
+                    .iter()
+                    .find(|next| old_ids.contains(next))
+                    .map(|next| next.to_string()),
+                html: new_child.to_string(),
+            }),
+        }
+    }
+    Ok(())
+}
+
+/// Diff children pairwise. Only possible if the structure has not changed.
+fn diff_child_list(old: &[Node], new: &[Node], patches: &mut Vec<Patch>) -> Result<(), ()> {
+    if old.len() != new.len() {
+        return Err(());
+    }
+    for (old_child, new_child) in old.iter().zip(new) {
+        match (old_child, new_child) {
+            (Node::Element(old_element), Node::Element(new_element)) => {
+                diff_element(old_element, new_element, patches)?
+            }
+            (old_child, new_child) if old_child == new_child => {}
+            _ => return Err(()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(node: Node) -> Element {
+        node.as_element().unwrap().clone()
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let node: Node = Element::new("div").id("a").child(Element::new("span").text("x")).into();
+        assert!(diff("a", &element(node.clone()), &node).is_empty());
+    }
+
+    #[test]
+    fn test_attributes() {
+        let old = Element::new("input").id("a").attr("value", "x").attr("disabled", true);
+        let new: Node = Element::new("input").id("a").attr("value", "y").data("role", "input").into();
+
+        assert_eq!(diff("a", &old, &new), [
+            Patch::RemoveAttribute { id: "a".into(), name: "disabled".into() },
+            Patch::SetAttribute { id: "a".into(), name: "value".into(), value: "y".into() },
+            Patch::SetValue { id: "a".into(), value: "y".into() },
+            Patch::SetAttribute { id: "a".into(), name: "data-role".into(), value: "input".into() },
+        ]);
+    }
+
+    #[test]
+    fn test_metro_data() {
+        let old = Element::new("input").id("a").data("role", "input").data("prepend", "Name");
+        let new: Node = Element::new("input").id("a").data("role", "input").data("prepend", "User").into();
+        assert_eq!(diff("a", &old, &new), [replace("a", new.as_element().unwrap())]);
+    }
+
+    #[test]
+    fn test_checked() {
+        let old = Element::new("input").id("a").data("role", "checkbox").data("indeterminate", true);
+        let new: Node = Element::new("input").id("a").data("role", "checkbox").data("indeterminate", false)
+            .attr("checked", "checked").into();
+        assert_eq!(diff("a", &old, &new), [
+            Patch::SetAttribute { id: "a".into(), name: "data-indeterminate".into(), value: "false".into() },
+            Patch::SetAttribute { id: "a".into(), name: "checked".into(), value: "checked".into() },
+            Patch::SetChecked { id: "a".into(), checked: true },
+        ]);
+        assert!(diff("a", new.as_element().unwrap(), &old.into()).contains(&Patch::SetChecked {
+            id: "a".into(),
+            checked: false,
+        }));
+    }
+
+    #[test]
+    fn test_content() {
+        let old = Element::new("a").id("a").text("Start");
+        let new: Node = Element::new("a").id("a").text("<Stop>").into();
+        assert_eq!(diff("a", &old, &new), [
+            Patch::SetContent { id: "a".into(), html: "&lt;Stop&gt;".into() },
+        ]);
+
+        let old = Element::new("textarea").id("t").text("old");
+        let new: Node = Element::new("textarea").id("t").text("new").into();
+        assert_eq!(diff("t", &old, &new), [
+            Patch::SetValue { id: "t".into(), value: "new".into() },
+        ]);
+    }
+
+    #[test]
+    fn test_nested_ids() {
+        let form = |label: &str| -> Node {
+            Element::new("form")
+                .id("f")
+                .child(Element::new("div").class("form-group").child(Element::new("a").id("b").text(label)))
+                .into()
+        };
+        assert_eq!(diff("f", &element(form("One")), &form("Two")), [
+            Patch::SetContent { id: "b".into(), html: "Two".into() },
+        ]);
+    }
+
+    #[test]
+    fn test_structure_changed() {
+        let old = Element::new("div").id("a").child(Element::new("span")).child(Element::new("span"));
+        let new: Node = Element::new("div").id("a").child(Element::new("span")).into();
+        assert_eq!(diff("a", &old, &new), [replace("a", new.as_element().unwrap())]);
+
+        let old = Element::new("div").id("a").child(Element::new("span").class("x"));
+        let new: Node = Element::new("div").id("a").child(Element::new("span").class("y")).into();
+        assert_eq!(diff("a", &old, &new), [replace("a", new.as_element().unwrap())]);
+    }
+
+    #[test]
+    fn test_keyed_children() {
+        let list = |ids: &[&str]| -> Node {
+            Element::new("ul")
+                .id("l")
+                .children(ids.iter().map(|id| Element::new("li").id(id).text(*id).into()))
+                .into()
+        };
+
+        assert_eq!(diff("l", &element(list(&["1", "2", "3"])), &list(&["1", "4", "3", "5"])), [
+            Patch::Remove { id: "2".into() },
+            Patch::Insert { parent: "l".into(), before: Some("3".into()), html: r#"<li id="4">4</li>"#.into() },
+            Patch::Insert { parent: "l".into(), before: None, html: r#"<li id="5">5</li>"#.into() },
+        ]);
+
+        // moving elements replaces the parent
+        let new = list(&["2", "1"]);
+        assert_eq!(diff("l", &element(list(&["1", "2"])), &new), [replace("l", new.as_element().unwrap())]);
+
+        // Metro widgets build their children themselves
+        let tree = |ids: &[&str]| -> Node { element(list(ids)).data("role", "treeview").into() };
+        let new = tree(&["3"]);
+        assert_eq!(diff("l", &element(tree(&["1"])), &new), [replace("l", new.as_element().unwrap())]);
+    }
+
+    #[test]
+    fn test_document() {
+        let mut document = Document::new();
+        document.set_content(Element::new("div").id("page").child(Element::new("a").id("b").text("One")).into());
+
+        let patches = document.update("b", Element::new("a").id("b").text("Two").into());
+        assert_eq!(patches, [Patch::SetContent { id: "b".into(), html: "Two".into() }]);
+        assert_eq!(document.content().unwrap().find_by_id("b").unwrap().text_content(), "Two");
+
+        let patches = document.update("c", Element::new("a").id("c").into());
+        assert_eq!(patches, [Patch::Replace { id: "c".into(), html: r#"<a id="c"></a>"#.into() }]);
+    }
+
+    #[test]
+    fn test_to_js() {
+        let patch = Patch::Insert { parent: "l".into(), before: None, html: "<li>O'Brien</li>".into() };
+        assert_eq!(patch.to_js(), r"insert_element('l', null, '\x3Cli\x3EO\'Brien\x3C/li\x3E')");
+    }
+}
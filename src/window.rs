@@ -0,0 +1,111 @@
+/// Options for the window of an app.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowOptions {
+    pub(crate) size: (i32, i32),
+    pub(crate) min_size: Option<(i32, i32)>,
+    pub(crate) resizable: bool,
+    pub(crate) fullscreen: bool,
+    pub(crate) frameless: bool,
+    pub(crate) background_color: Option<(u8, u8, u8, u8)>,
+    pub(crate) debug: bool,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        WindowOptions {
+            size: (800, 600),
+            min_size: None,
+            resizable: true,
+            fullscreen: false,
+            frameless: false,
+            background_color: None,
+            debug: cfg!(debug_assertions),
+        }
+    }
+}
+
+impl WindowOptions {
+    /// Options for a resizable window of 800x600 pixels. The developer tools
+    /// are only enabled in debug builds.
+    pub fn new() -> Self {
+        WindowOptions::default()
+    }
+
+    /// Set the initial size of the window.
+    pub fn size(mut self, width: i32, height: i32) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    /// Set the minimum size of the window content.
+    ///
+    /// The window is opened at least this large. As the window itself can not be
+    /// restricted, the content shows scroll bars if the window gets smaller.
+    pub fn min_size(mut self, width: i32, height: i32) -> Self {
+        self.min_size = Some((width, height));
+        self
+    }
+
+    /// Allow the user to resize the window.
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Open the window in fullscreen mode.
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Open the window without borders and title bar.
+    pub fn frameless(mut self, frameless: bool) -> Self {
+        self.frameless = frameless;
+        self
+    }
+
+    /// Set the background color of the window.
+    pub fn background_color(mut self, red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        self.background_color = Some((red, green, blue, alpha));
+        self
+    }
+
+    /// Enable the developer tools (inspector) of the web view.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// The size the window is opened with, respecting the minimum size.
+    pub(crate) fn initial_size(&self) -> (i32, i32) {
+        match self.min_size {
+            Some((min_width, min_height)) => (self.size.0.max(min_width), self.size.1.max(min_height)),
+            None => self.size,
+        }
+    }
+
+    /// CSS to be added to the page for these options.
+    pub(crate) fn css(&self) -> String {
+        match self.min_size {
+            Some((width, height)) => format!(
+                "body {{ min-width: {width}px; min-height: {height}px; }}",
+                width = width,
+                height = height
+            ),
+            None => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_size() {
+        let options = WindowOptions::new().size(400, 800).min_size(640, 480);
+        assert_eq!(options.initial_size(), (640, 800));
+        assert_eq!(options.css(), "body { min-width: 640px; min-height: 480px; }");
+        assert_eq!(WindowOptions::new().css(), "");
+    }
+}
@@ -1,21 +1,94 @@
 "use strict";
+
+/**
+ * The version of the messages sent to the app.
+ */
+var PROTOCOL_VERSION = 1;
+
 function fire_clicked(id) {
-    safe_invoke("{\"id\":" + JSON.stringify(id) + ",\"value\":\"Clicked\"}");
+    fire(id, "Clicked");
 }
 
 function fire_value_changed(id) {
-    safe_invoke("{\"id\":" + JSON.stringify(id) + ",\"value\":{\"ValueChanged\":" + JSON.stringify($("#" + id).val()) + "}}");
+    fire(id, {"ValueChanged": $("#" + id).val()});
+}
+
+function fire_checked_changed(id) {
+    var element = document.getElementById(id);
+    // a click ends the indeterminate state, which Metro styles by attribute
+    element.setAttribute('data-indeterminate', 'false');
+    fire(id, {"CheckedChanged": element.checked});
+}
+
+function fire_menu_item_clicked(idMenu, idItem) {
+    fire(idMenu, {"MenuItemClicked": idItem});
 }
 
 /**
  * Fired if a component has been created.
  */
 function fire_created() {
-    safe_invoke("{\"id\":" + JSON.stringify(this.id) + ",\"value\":\"Created\"}");
+    fire(this.id, "Created");
+}
+
+/**
+ * Fired if a tree view has been created: adds the check boxes of a checkable tree
+ * and lets a double click rename the nodes of an editable tree.
+ */
+function tree_created() {
+    var tree = $(this);
+    var idTree = tree.attr('id');
+    if (tree.attr('data-checkable') === 'true') {
+        tree.find('li').each(function () {
+            add_tree_check(idTree, $(this));
+        });
+    }
+    if (tree.attr('data-editable') === 'true') {
+        // a double click renames the node instead of toggling it
+        this.addEventListener('dblclick', function (event) {
+            var caption = event.target.closest('.caption');
+            if (caption !== null && this.contains(caption) && !$(event.target).hasClass('tree-edit')) {
+                event.stopPropagation();
+                start_tree_edit(idTree, $(caption.parentNode));
+            }
+        }, true);
+    }
+    fire_created.call(this);
+}
+
+/**
+ * Add a check box in front of the caption of a tree node.
+ */
+function add_tree_check(idTree, node) {
+    var check = $('<input type="checkbox">').on('click', function (event) {
+        // the tree propagates the check state itself
+        event.stopPropagation();
+        if (node.hasClass('disabled')) {
+            event.preventDefault();
+            return;
+        }
+        fire(idTree, {"NodeChecked": {"node": node.attr('id'), "checked": this.checked}});
+    });
+    node.children('.caption').prepend(check);
+}
+
+/**
+ * Show the check states of tree nodes.
+ * @param idTree the id of the tree.
+ * @param checks pairs of a node id and 'checked', 'unchecked' or 'indeterminate'.
+ */
+function set_tree_checks(idTree, checks) {
+    checks.forEach(function (check) {
+        var input = $('#'+check[0]).children('.caption').children('input[type=checkbox]')[0];
+        if (input !== undefined) {
+            input.checked = check[1] === 'checked';
+            input.indeterminate = check[1] === 'indeterminate';
+        }
+    });
 }
 
 function fire_page_loaded() {
-    safe_invoke("{\"id\":" + JSON.stringify('App') + ",\"value\":\"PageLoaded\"}");
+    fire('App', "PageLoaded");
 }
 
 
@@ -24,11 +97,28 @@ function fire_page_loaded() {
  * @param node element clicked in tree.
  */
 function fire_node_clicked(node) {
-    safe_invoke("{\"id\":" + JSON.stringify(this.id) + ",\"value\":{\"ChildClicked\":" + JSON.stringify(node.id) + "}}");
+    var e = window.event || {};
+    fire(this.id, {"NodeClicked": {"node": node.id, "ctrl": !!(e.ctrlKey || e.metaKey), "shift": !!e.shiftKey}});
 }
 
 function fire_node_expand(node) {
-    safe_invoke("{\"id\":" + JSON.stringify(this.id) + ",\"value\":{\"NodeExpand\":" + JSON.stringify(node.id) + "}}");
+    fire(this.id, {"NodeExpand": node.id});
+}
+
+function fire_node_collapse(node) {
+    // new nodes get collapsed before they have an id
+    if (node.id) {
+        fire(this.id, {"NodeCollapse": node.id});
+    }
+}
+
+/**
+ * Send an event for a component to the app.
+ * @param id id of the component.
+ * @param value the value of the event.
+ */
+function fire(id, value) {
+    safe_invoke(JSON.stringify({"version": PROTOCOL_VERSION, "event": {"id": id, "value": value}}));
 }
 
 
@@ -45,6 +135,221 @@ function safe_invoke(str) {
     }
 }
 
+/**
+ * Highlight the selected nodes of a tree.
+ * @param idTree id of the tree.
+ * @param ids ids of the selected nodes.
+ * @param idFocus id of the node keyboard navigation starts from or null.
+ */
+function set_tree_selection(idTree, ids, idFocus) {
+    var tree = document.getElementById(idTree);
+    if (tree === null) {
+        return;
+    }
+    $(tree).find('li').removeClass('current');
+    ids.forEach(function (id) {
+        $(document.getElementById(id)).addClass('current');
+    });
+    tree.denshiFocus = idFocus;
+}
+
+/**
+ * Keyboard navigation in a tree: up and down move the selection, right and left
+ * expand and collapse nodes, F2 renames a node of an editable tree.
+ */
+function tree_key_down(event, idTree) {
+    if ($(event.target).hasClass('tree-edit')) {
+        return;
+    }
+    var tree = document.getElementById(idTree);
+    var treeview = $(tree).data('treeview');
+    var nodes = $(tree).find('li:visible').not('.disabled').toArray();
+    var focus = document.getElementById(tree.denshiFocus);
+    var index = nodes.indexOf(focus);
+    var target = null;
+
+    switch (event.key) {
+        case 'ArrowDown':
+            target = nodes[Math.min(index + 1, nodes.length - 1)];
+            break;
+        case 'ArrowUp':
+            target = nodes[Math.max(index - 1, 0)];
+            break;
+        case 'Home':
+            target = nodes[0];
+            break;
+        case 'End':
+            target = nodes[nodes.length - 1];
+            break;
+        case 'ArrowRight':
+            if (focus !== null && $(focus).children('.node-toggle').length > 0 && !$(focus).hasClass('expanded')) {
+                treeview.toggleNode(focus);
+            }
+            break;
+        case 'ArrowLeft':
+            if (focus !== null && $(focus).hasClass('expanded')) {
+                treeview.toggleNode(focus);
+            } else if (focus !== null) {
+                target = $(focus).parent().closest('li:not(.disabled)', tree)[0];
+            }
+            break;
+        case 'F2':
+            if (focus !== null && tree.getAttribute('data-editable') === 'true') {
+                start_tree_edit(idTree, $(focus));
+            }
+            break;
+        default:
+            return;
+    }
+    event.preventDefault();
+    if (target !== undefined && target !== null && target !== focus) {
+        fire(idTree, {"NodeClicked": {"node": target.id, "ctrl": false, "shift": event.shiftKey}});
+    }
+}
+
+/**
+ * Replace the caption of a tree node by an input to rename the node. Enter
+ * sends the new name to the tree, escape or leaving the input cancels it.
+ * @param idTree the id of the tree.
+ * @param node the tree node as jQuery object.
+ */
+function start_tree_edit(idTree, node) {
+    var caption = node.children('.caption')[0];
+    if (node.hasClass('disabled') || caption === undefined || caption.querySelector('.tree-edit') !== null) {
+        return;
+    }
+    var content = Array.prototype.filter.call(caption.childNodes, function (child) {
+        return !(child.tagName === 'INPUT' && child.type === 'checkbox');
+    });
+    var edit = document.createElement('input');
+    edit.type = 'text';
+    edit.className = 'tree-edit';
+    edit.value = content.map(function (child) {
+        return child.textContent;
+    }).join('');
+    content.forEach(function (child) {
+        caption.removeChild(child);
+    });
+    caption.appendChild(edit);
+
+    var cancel = function () {
+        // the input is gone if the node has been renamed
+        if (edit.parentNode !== caption) {
+            return;
+        }
+        caption.removeChild(edit);
+        content.forEach(function (child) {
+            caption.appendChild(child);
+        });
+    };
+    edit.addEventListener('keydown', function (event) {
+        event.stopPropagation();
+        if (event.key === 'Enter') {
+            fire(idTree, {"NodeRenamed": {"node": node.attr('id'), "name": edit.value}});
+        } else if (event.key === 'Escape') {
+            cancel();
+            document.getElementById(idTree).focus();
+        }
+    });
+    edit.addEventListener('blur', cancel);
+    ['click', 'dblclick'].forEach(function (type) {
+        edit.addEventListener(type, function (event) {
+            event.stopPropagation();
+        });
+    });
+    edit.focus();
+    edit.select();
+}
+
+/**
+ * Keep editing the caption of a tree node after a rename has been rejected.
+ * @param idNode the id of the node.
+ * @param message the HTML telling why the name has been rejected.
+ */
+function show_tree_edit_error(idNode, message) {
+    var edit = $('#'+idNode).children('.caption').children('.tree-edit')[0];
+    if (edit !== undefined) {
+        edit.classList.add('invalid');
+        edit.focus();
+    }
+    Metro.toast.create(message, null, 5000, 'alert');
+}
+
+/**
+ * The node currently dragged: the ids of its tree, the node and the nodes it can be dropped onto.
+ */
+var treeDrag = null;
+
+/**
+ * Start dragging a tree node.
+ * @param event the dragstart event.
+ * @param idTree the id of the tree.
+ */
+function tree_drag_start(event, idTree) {
+    var node = $(event.target).closest('li')[0];
+    if (node === undefined || $(node).hasClass('disabled')) {
+        event.preventDefault();
+        return;
+    }
+    treeDrag = {tree: idTree, node: node.id, targets: []};
+    event.dataTransfer.effectAllowed = 'move';
+    event.dataTransfer.setData('text/plain', $(node).children('.caption').text());
+    fire(idTree, {"NodeDragStart": node.id});
+}
+
+/**
+ * Set the nodes the dragged node can be dropped onto.
+ */
+function set_tree_drop_targets(idTree, idNodes) {
+    if (treeDrag !== null && treeDrag.tree === idTree) {
+        treeDrag.targets = idNodes;
+    }
+}
+
+/**
+ * Return the element the dragged tree node would be dropped onto or null: a node
+ * of its tree accepting it, another tree or the closest element with an id outside the tree.
+ */
+function tree_drop_target(event) {
+    var tree = document.getElementById(treeDrag.tree);
+    if (tree !== null && tree.contains(event.target)) {
+        var node = $(event.target).closest('li')[0];
+        return node !== undefined && treeDrag.targets.indexOf(node.id) >= 0 ? node : null;
+    }
+    // the ids of the nodes of other trees are internal to them
+    var other = $(event.target).closest('[data-role=treeview]')[0];
+    var element = other !== undefined ? other : $(event.target).closest('[id]')[0];
+    return element === undefined ? null : element;
+}
+
+document.addEventListener('dragover', function (event) {
+    if (treeDrag === null) {
+        return;
+    }
+    $('.drop-target').removeClass('drop-target');
+    var target = tree_drop_target(event);
+    if (target !== null) {
+        event.preventDefault();
+        $(target).addClass('drop-target');
+    }
+});
+
+document.addEventListener('drop', function (event) {
+    if (treeDrag === null) {
+        return;
+    }
+    var target = tree_drop_target(event);
+    if (target !== null) {
+        event.preventDefault();
+        fire(treeDrag.tree, {"NodeDropped": {"node": treeDrag.node, "target": target.id}});
+    }
+});
+
+document.addEventListener('dragend', function () {
+    $('.drop-target').removeClass('drop-target');
+    treeDrag = null;
+});
+
 function clear_node(idTree, idNode) {
     var tree = $('#'+idTree);
     var parentNode = $('#'+idNode);
@@ -52,20 +357,250 @@ function clear_node(idTree, idNode) {
     tree.data('treeview').clean(parentNode);
 }
 
-function add_tree_node(idTree, idParent, idNode, caption, hasChildren) {
+function add_tree_node(idTree, idParent, idNode, caption, hasChildren, icon, tooltip, cssClass, enabled) {
+    insert_tree_node(idTree, idParent, null, idNode, caption, hasChildren, icon, tooltip, cssClass, enabled);
+}
+
+/**
+ * Insert a node into a tree view.
+ * @param idTree the id of the tree.
+ * @param idParent the id of the parent node or null for a root.
+ * @param idBefore the id of the following sibling or null to append the node.
+ * @param idNode the id of the new node.
+ * @param caption the caption of the new node.
+ * @param hasChildren whether the node can be expanded.
+ * @param icon the HTML of the icon or null.
+ * @param tooltip the tooltip or null.
+ * @param cssClass additional CSS classes or null.
+ * @param enabled whether the node can be selected.
+ */
+function insert_tree_node(idTree, idParent, idBefore, idNode, caption, hasChildren, icon, tooltip, cssClass, enabled) {
     var tree = $('#'+idTree);
-    var parentNode = $('#'+idParent);
+    var treeview = tree.data('treeview');
 
-    var new_node = tree.data('treeview').addTo(parentNode, {
-        caption: caption
-    });
+    var data = {caption: caption};
+    if (icon !== null) {
+        data.icon = icon;
+    }
+    var new_node;
+    if (idBefore !== null) {
+        new_node = treeview.insertBefore($('#'+idBefore), data);
+    } else {
+        new_node = treeview.addTo(idParent === null ? null : $('#'+idParent), data);
+    }
 
     if (hasChildren) {
-        tree.data('treeview').addTo(new_node, {
+        treeview.addTo(new_node, {
             caption: ''
         });
-        tree.data('treeview').toggleNode(new_node);
+        treeview.toggleNode(new_node);
     }
 
     new_node.id(idNode);
-}
\ No newline at end of file
+    if (tree.attr('data-checkable') === 'true') {
+        add_tree_check(idTree, new_node);
+    }
+    if (tree.attr('data-draggable') === 'true') {
+        new_node.attr('draggable', 'true');
+    }
+    set_tree_node_style(new_node, tooltip, cssClass, enabled);
+}
+
+/**
+ * Apply the tooltip, additional CSS classes and enabled state to a tree node.
+ * Classes applied before are replaced.
+ */
+function set_tree_node_style(node, tooltip, cssClass, enabled) {
+    if (tooltip !== null) {
+        node.attr('title', tooltip);
+    } else {
+        node.removeAttr('title');
+    }
+    var previous = node.attr('data-css-class');
+    if (previous) {
+        node.removeClass(previous);
+    }
+    if (cssClass !== null) {
+        node.addClass(cssClass).attr('data-css-class', cssClass);
+    } else {
+        node.removeAttr('data-css-class');
+    }
+    node.toggleClass('disabled', !enabled);
+}
+
+/**
+ * Show a disabled placeholder node while the children of a node are loaded.
+ */
+function add_tree_loading_node(idTree, idParent, idNode, caption) {
+    var new_node = $('#'+idTree).data('treeview').addTo($('#'+idParent), {caption: caption});
+    new_node.id(idNode);
+    new_node.addClass('disabled tree-loading');
+}
+
+/**
+ * Tell the user why the children of a tree node could not be loaded. The node
+ * can be expanded again to retry.
+ * @param message the HTML telling what went wrong.
+ */
+function show_tree_load_error(idTree, idParent, message) {
+    set_tree_node_expandable(idTree, idParent);
+    Metro.toast.create(message, null, 5000, 'alert');
+}
+
+/**
+ * Remove a node with all its children from a tree view.
+ */
+function remove_tree_node(idTree, idNode) {
+    $('#'+idTree).data('treeview').del($('#'+idNode));
+}
+
+/**
+ * Move a tree node with its children among its siblings.
+ * @param idBefore the id of the following sibling or null to move the node to the end.
+ */
+function move_tree_node(idNode, idBefore) {
+    var node = document.getElementById(idNode);
+    var before = idBefore === null ? null : document.getElementById(idBefore);
+    node.parentNode.insertBefore(node, before);
+}
+
+/**
+ * Show a new caption, icon and style of a tree node.
+ * @param icon the HTML of the icon or null.
+ */
+function update_tree_node(idNode, caption, icon, tooltip, cssClass, enabled) {
+    var node = $('#'+idNode);
+    var captionElement = node.children('.caption')[0];
+    var check = captionElement.querySelector(':scope > input[type=checkbox]');
+    captionElement.innerHTML = caption;
+    if (check !== null) {
+        captionElement.insertBefore(check, captionElement.firstChild);
+    }
+    node.children('.icon').remove();
+    if (icon !== null) {
+        $('<span>').addClass('icon').html(icon).insertBefore(node.children('.caption'));
+    }
+    set_tree_node_style(node, tooltip, cssClass, enabled);
+}
+
+/**
+ * Let a tree node without children be expanded, its children are loaded on expansion.
+ */
+function set_tree_node_expandable(idTree, idNode) {
+    var treeview = $('#'+idTree).data('treeview');
+    var node = $('#'+idNode);
+    treeview.addTo(node, {
+        caption: ''
+    });
+    treeview.toggleNode(node);
+}
+
+/**
+ * Metro wraps some widgets (inputs, check boxes, panels, ...) into a container
+ * carrying the widget's role as class. Return this container or the element itself.
+ * @param element the root element of a component.
+ */
+function component_container(element) {
+    var role = element.getAttribute('data-role');
+    var parent = element.parentElement;
+    if (role !== null && parent !== null && parent.classList.contains(role) && !element.classList.contains(role)) {
+        return parent;
+    }
+    return element;
+}
+
+/**
+ * Replace a rendered component by new markup.
+ * @param id id of the component's root element.
+ * @param html the newly rendered component.
+ */
+function replace_element(id, html) {
+    var element = document.getElementById(id);
+    if (element !== null) {
+        component_container(element).outerHTML = html;
+    }
+}
+
+/**
+ * Set an attribute of an element.
+ */
+function set_attribute(id, name, value) {
+    var element = document.getElementById(id);
+    if (element !== null) {
+        element.setAttribute(name, value);
+    }
+}
+
+/**
+ * Remove an attribute of an element.
+ */
+function remove_attribute(id, name) {
+    var element = document.getElementById(id);
+    if (element !== null) {
+        element.removeAttribute(name);
+    }
+}
+
+/**
+ * Set the current value of an input or text area.
+ */
+function set_value(id, value) {
+    var element = document.getElementById(id);
+    if (element !== null && element.value !== value) {
+        element.value = value;
+    }
+}
+
+/**
+ * Set whether a check box is checked.
+ */
+function set_checked(id, checked) {
+    var element = document.getElementById(id);
+    if (element !== null) {
+        element.checked = checked;
+    }
+}
+
+/**
+ * Replace the content of an element.
+ */
+function set_content(id, html) {
+    var element = document.getElementById(id);
+    if (element !== null) {
+        element.innerHTML = html;
+    }
+}
+
+/**
+ * Insert new markup into a parent element.
+ * @param idParent id of the parent element.
+ * @param idBefore id of the sibling to insert before or null to append.
+ * @param html the markup to insert.
+ */
+function insert_element(idParent, idBefore, html) {
+    var parent = document.getElementById(idParent);
+    var before = idBefore === null ? null : document.getElementById(idBefore);
+    if (before !== null) {
+        component_container(before).insertAdjacentHTML('beforebegin', html);
+    }
+    else if (parent !== null) {
+        parent.insertAdjacentHTML('beforeend', html);
+    }
+}
+
+/**
+ * Remove an element.
+ */
+function remove_element(id) {
+    var element = document.getElementById(id);
+    if (element !== null) {
+        component_container(element).remove();
+    }
+}
+
+/**
+ * Send the current DOM of the page to the app for dumping it.
+ */
+function dump_dom() {
+    fire('App', {"DomDumped": "<!DOCTYPE html>\n" + document.documentElement.outerHTML});
+}